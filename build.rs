@@ -0,0 +1,41 @@
+use std::{path::Path, process::Command};
+
+fn main() {
+    // Expose the target triple and git commit to the build-info command.
+    println!(
+        "cargo:rustc-env=TARGET={}",
+        std::env::var("TARGET").unwrap()
+    );
+    println!(
+        "cargo:rustc-env=GIT_COMMIT={}",
+        git_commit().unwrap_or_else(|| "unknown".to_string())
+    );
+
+    println!("cargo:rerun-if-changed=build.rs");
+    // Rerun when another commit is checked out or a commit is made on the current branch.
+    let head = Path::new(".git/HEAD");
+    if head.exists() {
+        println!("cargo:rerun-if-changed={}", head.display());
+        let branch = std::fs::read_to_string(head).ok().and_then(|head| {
+            head.strip_prefix("ref: ")
+                .map(|branch| branch.trim().to_string())
+        });
+        if let Some(branch) = branch.map(|branch| Path::new(".git").join(branch)) {
+            if branch.exists() {
+                println!("cargo:rerun-if-changed={}", branch.display());
+            }
+        }
+    }
+}
+
+/// Short hash of the checked out commit, None outside of a git checkout.
+fn git_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8(output.stdout).ok()?.trim().to_string())
+}
@@ -2,7 +2,15 @@ use clap::Parser;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    env_logger::init();
     let args = ceramic_pocket_knife::Cli::parse();
+    let mut logger = match args.log_filter() {
+        Some(filter) => {
+            let mut logger = env_logger::Builder::new();
+            logger.parse_filters(filter);
+            logger
+        }
+        None => env_logger::Builder::from_default_env(),
+    };
+    logger.init();
     ceramic_pocket_knife::run(args, tokio::io::stdin(), tokio::io::stdout()).await
 }
@@ -1,35 +1,70 @@
-use std::str::FromStr;
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Context, Result};
 use ceramic_core::{Cid, EventId, Interest, StreamId, StreamIdType};
 use ceramic_event::unvalidated;
+use dag_jose::DagJoseCodec;
 use futures::pin_mut;
-use ipld_core::ipld::Ipld;
-use libp2p_identity::PeerId;
+use ipld_core::{codec::Codec, ipld::Ipld};
+use iroh_car::{CarHeader, CarWriter};
+use libp2p_identity::{ed25519, PeerId};
 use multibase::Base;
-use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use multihash_codetable::{Code, MultihashDigest};
+use rand::{distributions::Alphanumeric, rngs::StdRng, Rng};
 use recon::Key;
+use serde::Serialize;
+use serde_ipld_dagcbor::codec::DagCborCodec;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
 use crate::{
     cli::{
-        Command, EventIdGenerateArgs, EventIdInspectArgs, InterestInspectArgs, Network,
-        StreamIdCreateArgs, StreamIdGenerateArgs, StreamIdInspectArgs, StreamType,
+        AccountRelation, Command, DataCommitCreateArgs, DidKeySignArgs, DidKeyVerifyArgs,
+        DidPkhGenerateArgs, EventIdCreateArgs, EventIdGenerateArgs, EventIdInspectArgs,
+        GenesisCommitCreateArgs, InterestInspectArgs, JwsFormat, JwsPayload, JwsSignArgs,
+        JwsVerifyArgs, ModelDefinitionCreateArgs, Network, NetworkInspectArgs, OutputFormat,
+        PeerIdFromKeyArgs, StreamIdCreateArgs, StreamIdFromBytesArgs, StreamIdGenerateArgs,
+        StreamIdInspectArgs, StreamType,
     },
-    random_cid,
+    random_cid, read_car, RAW_CODEC,
 };
 
+/// Stream ID of the meta model, the model of all Model streams
+const METAMODEL_STREAM_ID: &str = "kh4q0ozorrgaq2mezktnrmdwleo1d";
+/// Varint encoded multicodec prefix of an ed25519 public key
+const ED25519_PUB_PREFIX: [u8; 2] = [0xed, 0x01];
+/// Multicodec code for Ceramic, the first varint of every event ID
+const EVENT_ID_CODEC: u64 = 0xce;
+/// Ceramic type code for event IDs, the second varint of every event ID
+const EVENT_ID_VERSION: u64 = 0x05;
+
 pub enum Operation {
     StreamIdCreate(StreamIdCreateArgs),
     StreamIdInspect(StreamIdInspectArgs),
     StreamIdGenerate(StreamIdGenerateArgs),
-    StreamIdFromBytes,
+    StreamIdFromBytes(StreamIdFromBytesArgs),
+    StreamIdFromGenesis,
     EventIdGenerate(EventIdGenerateArgs),
+    EventIdCreate(EventIdCreateArgs),
     EventIdInspect(EventIdInspectArgs),
+    NetworkInspect(NetworkInspectArgs),
     EventInspect,
     InterestInspect(InterestInspectArgs),
     DidKeyGenerate,
+    DidPkhGenerate(DidPkhGenerateArgs),
     PeerIdGenerate,
+    KeypairGenerate,
+    PeerIdFromKey(PeerIdFromKeyArgs),
+    JwsSign(JwsSignArgs),
+    JwsVerify(JwsVerifyArgs),
+    DidKeySign(DidKeySignArgs),
+    DidKeyVerify(DidKeyVerifyArgs),
+    GenesisCommitCreate(GenesisCommitCreateArgs),
+    DataCommitCreate(DataCommitCreateArgs),
+    ModelDefinitionCreate(ModelDefinitionCreateArgs),
 }
 
 impl TryFrom<Command> for Operation {
@@ -40,107 +75,590 @@ impl TryFrom<Command> for Operation {
             Command::StreamIdCreate(args) => Ok(Operation::StreamIdCreate(args)),
             Command::StreamIdInspect(args) => Ok(Operation::StreamIdInspect(args)),
             Command::StreamIdGenerate(args) => Ok(Operation::StreamIdGenerate(args)),
-            Command::StreamIdFromBytes => Ok(Operation::StreamIdFromBytes),
+            Command::StreamIdFromBytes(args) => Ok(Operation::StreamIdFromBytes(args)),
+            Command::StreamIdFromGenesis => Ok(Operation::StreamIdFromGenesis),
             Command::EventIdGenerate(args) => Ok(Operation::EventIdGenerate(args)),
+            Command::EventIdCreate(args) => Ok(Operation::EventIdCreate(args)),
             Command::EventIdInspect(args) => Ok(Operation::EventIdInspect(args)),
+            Command::NetworkInspect(args) => Ok(Operation::NetworkInspect(args)),
             Command::EventInspect => Ok(Operation::EventInspect),
             Command::InterestInspect(args) => Ok(Operation::InterestInspect(args)),
             Command::DidKeyGenerate => Ok(Operation::DidKeyGenerate),
+            Command::DidPkhGenerate(args) => Ok(Operation::DidPkhGenerate(args)),
             Command::PeerIdGenerate => Ok(Operation::PeerIdGenerate),
+            Command::KeypairGenerate => Ok(Operation::KeypairGenerate),
+            Command::PeerIdFromKey(args) => Ok(Operation::PeerIdFromKey(args)),
+            Command::JwsSign(args) => Ok(Operation::JwsSign(args)),
+            Command::JwsVerify(args) => Ok(Operation::JwsVerify(args)),
+            Command::DidKeySign(args) => Ok(Operation::DidKeySign(args)),
+            Command::DidKeyVerify(args) => Ok(Operation::DidKeyVerify(args)),
+            Command::GenesisCommitCreate(args) => Ok(Operation::GenesisCommitCreate(args)),
+            Command::DataCommitCreate(args) => Ok(Operation::DataCommitCreate(args)),
+            Command::ModelDefinitionCreate(args) => Ok(Operation::ModelDefinitionCreate(args)),
             _ => Err(value),
         }
     }
 }
 
-pub async fn run(op: Operation, stdin: impl AsyncRead, stdout: impl AsyncWrite) -> Result<()> {
+pub async fn run(
+    op: Operation,
+    format: OutputFormat,
+    mut rng: StdRng,
+    stdin: impl AsyncRead + Send,
+    stdout: impl AsyncWrite,
+) -> Result<()> {
     pin_mut!(stdin, stdout);
     match op {
         Operation::StreamIdCreate(args) => {
+            let cid = Cid::from_str(&args.cid)
+                .with_context(|| format!("failed to parse init CID '{}'", args.cid))?;
+            let codec = cid.codec();
+            if codec != <DagCborCodec as Codec<Ipld>>::CODE
+                && codec != <DagJoseCodec as Codec<Ipld>>::CODE
+            {
+                log::warn!(
+                    "init CID codec 0x{codec:x} is not dag-cbor or dag-jose, \
+                     the CID is not likely a Ceramic commit"
+                );
+            }
             let stream_id = StreamId {
                 r#type: convert_type(args.r#type),
-                cid: Cid::from_str(&args.cid)?,
+                cid,
             };
             stdout
                 .write_all(format!("{stream_id}\n").as_bytes())
                 .await?;
         }
         Operation::StreamIdInspect(args) => {
-            let stream_id = StreamId::from_str(&args.id)?;
-            stdout
-                .write_all(format!("{:?}\n", stream_id).as_bytes())
-                .await?;
+            let stream_id = StreamId::from_str(&args.id)
+                .with_context(|| format!("failed to parse stream ID '{}'", args.id))?;
+            let out = match format {
+                OutputFormat::Text => format!("{:?}", stream_id),
+                OutputFormat::Json => serde_json::to_string(&StreamIdInfo {
+                    stream_id: stream_id.to_string(),
+                    r#type: format!("{:?}", stream_id.r#type),
+                    cid: stream_id.cid.to_string(),
+                })?,
+            };
+            stdout.write_all(format!("{out}\n").as_bytes()).await?;
         }
         Operation::StreamIdGenerate(args) => {
             let stream_id = StreamId {
                 r#type: convert_type(args.r#type),
-                cid: random_cid(),
+                cid: random_cid(&mut rng),
             };
             stdout
                 .write_all(format!("{stream_id}\n").as_bytes())
                 .await?;
         }
-        Operation::StreamIdFromBytes => {
+        Operation::StreamIdFromBytes(args) => {
             let mut bytes = Vec::new();
-            stdin.read_to_end(&mut bytes).await?;
-            let stream_id = StreamId::try_from(bytes.as_slice())?;
+            stdin
+                .read_to_end(&mut bytes)
+                .await
+                .context("failed to read stream ID bytes from stdin")?;
+            let bytes = args.encoding.decode(bytes)?;
+            let stream_id = StreamId::try_from(bytes.as_slice())
+                .context("failed to decode stream ID from bytes")?;
             stdout
                 .write_all(format!("{}\n", stream_id).as_bytes())
                 .await?;
         }
+        Operation::StreamIdFromGenesis => {
+            let (roots, blocks) = read_car(stdin)
+                .await
+                .context("failed to read genesis CAR file")?;
+            let root = *roots
+                .first()
+                .ok_or_else(|| anyhow!("CAR file has no roots"))?;
+            let genesis = commit_payload(root, &blocks)
+                .with_context(|| format!("failed to load genesis commit {root}"))?;
+            let (_base, metamodel) = multibase::decode(METAMODEL_STREAM_ID)?;
+            let r#type = match genesis
+                .get("header")?
+                .and_then(|header| header.get("model").ok().flatten())
+            {
+                Some(Ipld::Bytes(model)) if *model == metamodel => StreamIdType::Model,
+                Some(Ipld::Bytes(_)) => StreamIdType::ModelInstanceDocument,
+                _ => bail!("genesis commit header does not have a model"),
+            };
+            let stream_id = StreamId { r#type, cid: root };
+            stdout
+                .write_all(format!("{stream_id}\n").as_bytes())
+                .await?;
+        }
         Operation::EventIdGenerate(args) => {
             let network = &convert_network(
                 args.network,
-                Some(args.local_network_id.unwrap_or_else(|| thread_rng().gen())),
-            );
-            let event_id = random_event_id(
-                network,
-                args.sort_key,
-                args.sort_value.and_then(|sort_value| {
-                    multibase::decode(sort_value)
-                        .ok()
-                        .map(|(_base, bytes)| bytes)
-                }),
-                args.controller,
-                args.init_id,
+                Some(args.local_network_id.unwrap_or_else(|| rng.gen())),
             )?;
+            let sort_value = args.sort_value.and_then(|sort_value| {
+                multibase::decode(sort_value)
+                    .ok()
+                    .map(|(_base, bytes)| bytes)
+            });
+            for _ in 0..args.count {
+                let event_id = random_event_id(
+                    &mut rng,
+                    network,
+                    args.sort_key.clone(),
+                    sort_value.clone(),
+                    args.controller.clone(),
+                    args.init_id.clone(),
+                )?;
+                stdout
+                    .write_all(format!("{}\n", event_id.to_hex()).as_bytes())
+                    .await?;
+            }
+        }
+        Operation::EventIdCreate(args) => {
+            let (_base, sort_value) = multibase::decode(&args.sort_value)
+                .with_context(|| format!("failed to decode sort value '{}'", args.sort_value))?;
+            let init_id = StreamId::from_str(&args.init_id)
+                .with_context(|| format!("failed to parse init ID '{}'", args.init_id))?;
+            let event_cid = Cid::from_str(&args.event_cid)
+                .with_context(|| format!("failed to parse event CID '{}'", args.event_cid))?;
+            let event_id = EventId::new(
+                &convert_network(args.network, args.local_network_id)?,
+                &args.sort_key,
+                &sort_value,
+                &args.controller,
+                &init_id.cid,
+                &event_cid,
+            );
             stdout
                 .write_all(format!("{}\n", event_id.to_hex()).as_bytes())
                 .await?;
         }
         Operation::EventIdInspect(args) => {
-            let (_base, bytes) = multibase::decode(args.event_id)?;
-            let event_id = EventId::try_from(bytes)?;
-            stdout
-                .write_all(format!("{:#?}\n", event_id).as_bytes())
-                .await?;
+            let (_base, bytes) = multibase::decode(&args.event_id)
+                .with_context(|| format!("failed to decode event ID '{}'", args.event_id))?;
+            let event_id = EventId::try_from(bytes)
+                .with_context(|| format!("failed to parse event ID '{}'", args.event_id))?;
+            let base = Base::from(args.value_base);
+            let encode = |value: Option<&[u8]>| value.map(|value| multibase::encode(base, value));
+            let info = EventIdInfo {
+                network_id: event_id.network_id(),
+                separator: encode(event_id.separator()),
+                controller: encode(event_id.controller()),
+                init_id: encode(event_id.stream_id()),
+                event_cid: event_id
+                    .cid()
+                    .map(|cid| cid.to_string_of_base(base))
+                    .transpose()?,
+            };
+            let out = match format {
+                OutputFormat::Text => {
+                    let unknown = || "(unknown)".to_string();
+                    format!(
+                        "Network ID: {}\nSeparator: {}\nController: {}\nInit ID: {}\nEvent CID: {}",
+                        info.network_id
+                            .map(|id| id.to_string())
+                            .unwrap_or_else(unknown),
+                        info.separator.unwrap_or_else(unknown),
+                        info.controller.unwrap_or_else(unknown),
+                        info.init_id.unwrap_or_else(unknown),
+                        info.event_cid.unwrap_or_else(unknown),
+                    )
+                }
+                OutputFormat::Json => serde_json::to_string(&info)?,
+            };
+            stdout.write_all(format!("{out}\n").as_bytes()).await?;
+        }
+        Operation::NetworkInspect(args) => {
+            let network = convert_network(args.network, args.local_network_id)?;
+            let local_id = match network {
+                ceramic_core::Network::Local(id) => Some(id),
+                _ => None,
+            };
+            // Event IDs start with the ceramic and event ID varints followed by the network ID.
+            let mut prefix = Vec::new();
+            for value in [EVENT_ID_CODEC, EVENT_ID_VERSION, network.id()] {
+                prefix.extend_from_slice(unsigned_varint::encode::u64(
+                    value,
+                    &mut unsigned_varint::encode::u64_buffer(),
+                ));
+            }
+            let prefix = hex::encode(prefix);
+            let out = match format {
+                OutputFormat::Text => {
+                    let mut out = format!(
+                        "Network: {}\nNetwork ID: {}\n",
+                        network.name(),
+                        network.id()
+                    );
+                    if let Some(id) = local_id {
+                        out.push_str(&format!("Local ID: {id}\n"));
+                    }
+                    out.push_str(&format!("Event ID Prefix: {prefix}"));
+                    out
+                }
+                OutputFormat::Json => serde_json::to_string(&NetworkInfo {
+                    network: network.name().to_string(),
+                    network_id: network.id(),
+                    local_id,
+                    event_id_prefix: prefix,
+                })?,
+            };
+            stdout.write_all(format!("{out}\n").as_bytes()).await?;
         }
         Operation::EventInspect => {
             let mut bytes = Vec::new();
-            stdin.read_to_end(&mut bytes).await?;
-            let (_cid, event) =
-                unvalidated::Event::<Ipld>::decode_car(bytes.as_slice(), false).await?;
-            stdout.write_all(format!("{event:#?}\n").as_bytes()).await?;
+            stdin
+                .read_to_end(&mut bytes)
+                .await
+                .context("failed to read event CAR file from stdin")?;
+            // Decoding validates the CAR file is a well formed event.
+            let (root, _event) = unvalidated::Event::<Ipld>::decode_car(bytes.as_slice(), false)
+                .await
+                .context("failed to decode event CAR file")?;
+            let (_roots, blocks) = read_car(bytes.as_slice()).await?;
+            let summary = EventSummary::new(root, &blocks)?;
+            let out = match format {
+                OutputFormat::Text => summary.to_text(),
+                OutputFormat::Json => serde_json::to_string(&summary)?,
+            };
+            stdout.write_all(format!("{out}\n").as_bytes()).await?;
         }
         Operation::InterestInspect(args) => {
-            let (_base, bytes) = multibase::decode(args.interest)?;
-            let interest = Interest::try_from(bytes)?;
-            stdout
-                .write_all(format!("{:#?}\n", interest).as_bytes())
-                .await?;
+            let (_base, bytes) = multibase::decode(&args.interest)
+                .with_context(|| format!("failed to decode interest '{}'", args.interest))?;
+            let interest = Interest::try_from(bytes)
+                .with_context(|| format!("failed to parse interest '{}'", args.interest))?;
+            let range = interest.range()?;
+            let contains = args
+                .contains
+                .map(|event_id| -> Result<bool> {
+                    let (_base, event_id) = multibase::decode(&event_id)
+                        .with_context(|| format!("failed to decode event ID '{event_id}'"))?;
+                    Ok(range.start <= event_id && event_id < range.end)
+                })
+                .transpose()?;
+            let out = if format == OutputFormat::Json {
+                serde_json::to_string(&InterestInfo {
+                    sort_key_hash: hex::encode(interest.sort_key_hash()?),
+                    peer_id: interest.peer_id()?.to_string(),
+                    range: InterestRange {
+                        start: multibase::encode(Base::Base16Lower, &range.start),
+                        end: multibase::encode(Base::Base16Lower, &range.end),
+                    },
+                    not_after: interest.not_after()?,
+                    contains,
+                })?
+            } else {
+                match contains {
+                    Some(contains) => format!("{interest:#?}\nContains: {contains}"),
+                    None => format!("{interest:#?}"),
+                }
+            };
+            stdout.write_all(format!("{out}\n").as_bytes()).await?;
         }
         Operation::DidKeyGenerate => {
             let mut buffer = [0; 32];
-            thread_rng().fill(&mut buffer);
+            rng.fill(&mut buffer);
             stdout
                 .write_all(
                     format!("did:key:{}\n", multibase::encode(Base::Base58Btc, buffer)).as_bytes(),
                 )
                 .await?;
         }
+        Operation::DidPkhGenerate(args) => {
+            let address = match args.address {
+                Some(address) => {
+                    let digits = address
+                        .strip_prefix("0x")
+                        .ok_or_else(|| anyhow!("address must start with 0x"))?;
+                    let bytes = hex::decode(digits)
+                        .with_context(|| format!("failed to decode address '{address}'"))?;
+                    if bytes.len() != 20 {
+                        bail!("address must be 20 bytes, found {} bytes", bytes.len());
+                    }
+                    let checksummed = checksum_address(&bytes);
+                    let mixed_case = digits.chars().any(|c| c.is_ascii_lowercase())
+                        && digits.chars().any(|c| c.is_ascii_uppercase());
+                    if mixed_case && checksummed != address {
+                        bail!("invalid address checksum, expected {checksummed}");
+                    }
+                    checksummed
+                }
+                None => {
+                    let mut bytes = [0u8; 20];
+                    rng.fill(&mut bytes);
+                    checksum_address(&bytes)
+                }
+            };
+            stdout
+                .write_all(format!("did:pkh:eip155:{}:{address}\n", args.chain_id).as_bytes())
+                .await?;
+        }
         Operation::PeerIdGenerate => {
-            let peer_id = PeerId::random();
+            let mut secret = [0u8; 32];
+            rng.fill(&mut secret);
+            let keypair: ed25519::Keypair = ed25519::SecretKey::try_from_bytes(secret)?.into();
+            let peer_id = libp2p_identity::PublicKey::from(keypair.public()).to_peer_id();
             stdout.write_all(format!("{peer_id}\n").as_bytes()).await?;
         }
+        Operation::KeypairGenerate => {
+            let mut secret = [0u8; 32];
+            rng.fill(&mut secret);
+            let keypair: ed25519::Keypair = ed25519::SecretKey::try_from_bytes(secret)?.into();
+            let private_key = hex::encode(keypair.secret());
+            let public_key = hex::encode(keypair.public().to_bytes());
+            let did = did_key(&keypair.public());
+            let peer_id = libp2p_identity::PublicKey::from(keypair.public()).to_peer_id();
+            let out = if format == OutputFormat::Json {
+                serde_json::to_string(&KeypairInfo {
+                    private_key,
+                    public_key,
+                    did,
+                    peer_id: peer_id.to_string(),
+                })?
+            } else {
+                format!(
+                    "Private Key: {private_key}\nPublic Key: {public_key}\nDID: {did}\nPeer ID: {peer_id}"
+                )
+            };
+            stdout.write_all(format!("{out}\n").as_bytes()).await?;
+        }
+        Operation::PeerIdFromKey(args) => {
+            let public = if args.key.starts_with("did:key:") {
+                did_key_public_key(&args.key)?
+            } else {
+                parse_private_key(&args.key)?.public()
+            };
+            let did = did_key(&public);
+            let peer_id = libp2p_identity::PublicKey::from(public).to_peer_id();
+            stdout
+                .write_all(format!("Peer ID: {peer_id}\nDID: {did}\n").as_bytes())
+                .await?;
+        }
+        Operation::JwsSign(args) => {
+            let keypair = parse_private_key(&args.key)?;
+            let controller = args
+                .controller
+                .unwrap_or_else(|| did_key(&keypair.public()));
+            let mut data = Vec::new();
+            stdin
+                .read_to_end(&mut data)
+                .await
+                .context("failed to read payload from stdin")?;
+            let (payload_cid, payload_block) = match args.payload {
+                JwsPayload::DagJson => {
+                    let dag_data: Ipld = serde_ipld_dagjson::from_slice(&data)
+                        .context("failed to parse payload as dag-json")?;
+                    let block = serde_ipld_dagcbor::to_vec(&dag_data)?;
+                    (
+                        block_cid(<DagCborCodec as Codec<Ipld>>::CODE, &block),
+                        block,
+                    )
+                }
+                JwsPayload::Raw => (block_cid(RAW_CODEC, &data), data),
+            };
+            match args.format {
+                JwsFormat::Compact => {
+                    let jws = Jws::sign(&keypair, &controller, &payload_cid)?;
+                    stdout
+                        .write_all(format!("{}\n", jws.to_compact()).as_bytes())
+                        .await?
+                }
+                JwsFormat::DagJose => {
+                    let jws = Jws::sign(&keypair, &controller, &payload_cid)?;
+                    stdout.write_all(&jws.to_dag_jose()?).await?
+                }
+                JwsFormat::Car => {
+                    let (_jose_cid, car) =
+                        signed_car(&keypair, &controller, payload_cid, payload_block).await?;
+                    stdout.write_all(&car).await?
+                }
+            };
+        }
+        Operation::JwsVerify(args) => {
+            let public_key = did_key_public_key(&args.did)?;
+            let mut data = Vec::new();
+            stdin
+                .read_to_end(&mut data)
+                .await
+                .context("failed to read JWS from stdin")?;
+            let jws = match args.format {
+                JwsFormat::Compact => Jws::from_compact(std::str::from_utf8(&data)?.trim())
+                    .context("failed to parse compact JWS")?,
+                JwsFormat::DagJose => {
+                    Jws::from_dag_jose(&data).context("failed to parse dag-jose JWS")?
+                }
+                JwsFormat::Car => {
+                    let (roots, blocks) = read_car(data.as_slice())
+                        .await
+                        .context("failed to read JWS CAR file")?;
+                    let root = roots
+                        .first()
+                        .ok_or_else(|| anyhow!("CAR file has no roots"))?;
+                    let block = blocks
+                        .get(root)
+                        .ok_or_else(|| anyhow!("CAR file is missing root block {root}"))?;
+                    let jws = Jws::from_dag_jose(block).context("failed to parse dag-jose JWS")?;
+                    let payload = Cid::try_from(jws.payload.as_slice())
+                        .context("JWS payload is not a CID")?;
+                    let payload_block = blocks
+                        .get(&payload)
+                        .ok_or_else(|| anyhow!("CAR file is missing payload block {payload}"))?;
+                    let code = Code::try_from(payload.hash().code())
+                        .with_context(|| format!("unsupported hash of payload {payload}"))?;
+                    if code.digest(payload_block) != *payload.hash() {
+                        bail!("payload block does not match {payload}");
+                    }
+                    jws
+                }
+            };
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+            let valid = jws.verify(&args.did, &public_key, now)?;
+            let payload = Cid::try_from(jws.payload.as_slice())
+                .map(|cid| cid.to_string())
+                .unwrap_or_else(|_| Base::Base64Url.encode(&jws.payload));
+            stdout
+                .write_all(
+                    format!(
+                        "Valid: {valid}\nHeader: {}\nPayload: {payload}\n",
+                        String::from_utf8_lossy(&jws.protected)
+                    )
+                    .as_bytes(),
+                )
+                .await?;
+            if !valid {
+                bail!("invalid signature");
+            }
+        }
+        Operation::DidKeySign(args) => {
+            let keypair = parse_private_key(&args.key)?;
+            if did_key(&keypair.public()) != args.did {
+                bail!("private key does not match {}", args.did);
+            }
+            let mut data = Vec::new();
+            stdin
+                .read_to_end(&mut data)
+                .await
+                .context("failed to read data from stdin")?;
+            let signature = keypair.sign(&data);
+            stdout
+                .write_all(format!("{}\n", Base::Base64Url.encode(signature)).as_bytes())
+                .await?;
+        }
+        Operation::DidKeyVerify(args) => {
+            let public_key = did_key_public_key(&args.did)?;
+            let signature = Base::Base64Url
+                .decode(args.signature.trim())
+                .context("failed to decode base64url signature")?;
+            let mut data = Vec::new();
+            stdin
+                .read_to_end(&mut data)
+                .await
+                .context("failed to read data from stdin")?;
+            let valid = public_key.verify(&data, &signature);
+            stdout
+                .write_all(format!("Valid: {valid}\n").as_bytes())
+                .await?;
+            if !valid {
+                bail!("invalid signature");
+            }
+        }
+        Operation::GenesisCommitCreate(args) => {
+            let keypair = parse_private_key(&args.key)?;
+            let controller = args
+                .controller
+                .unwrap_or_else(|| did_key(&keypair.public()));
+            let data: Ipld = args
+                .content
+                .map(|content| serde_ipld_dagjson::from_slice(content.as_bytes()))
+                .transpose()
+                .context("failed to parse content as dag-json")?
+                .unwrap_or(Ipld::Null);
+            let unique = matches!(args.account_relation, AccountRelation::List);
+            let payload = genesis_payload(
+                &mut rng,
+                &args.r#type,
+                &controller,
+                args.model,
+                unique,
+                data,
+            )?;
+            let (cid, car) = signed_commit_car(&keypair, &controller, &payload).await?;
+            let stream_id = StreamId {
+                r#type: convert_type(args.r#type),
+                cid,
+            };
+            log::info!("stream ID: {stream_id}");
+            stdout.write_all(&car).await?;
+        }
+        Operation::ModelDefinitionCreate(args) => {
+            let keypair = parse_private_key(&args.key)?;
+            let controller = args
+                .controller
+                .unwrap_or_else(|| did_key(&keypair.public()));
+            let mut data = Vec::new();
+            stdin
+                .read_to_end(&mut data)
+                .await
+                .context("failed to read schema from stdin")?;
+            let schema: Ipld = serde_ipld_dagjson::from_slice(&data)
+                .context("failed to parse schema as dag-json")?;
+            validate_schema(&schema)?;
+            let account_relation = match args.account_relation {
+                AccountRelation::List => "list",
+                AccountRelation::Single => "single",
+            };
+            let mut content = BTreeMap::from([
+                ("version".to_string(), Ipld::String("1.0".to_string())),
+                ("name".to_string(), Ipld::String(args.name)),
+                (
+                    "accountRelation".to_string(),
+                    Ipld::Map(BTreeMap::from([(
+                        "type".to_string(),
+                        Ipld::String(account_relation.to_string()),
+                    )])),
+                ),
+                ("schema".to_string(), schema),
+                ("views".to_string(), Ipld::Map(BTreeMap::new())),
+            ]);
+            if let Some(description) = args.description {
+                content.insert("description".to_string(), Ipld::String(description));
+            }
+            let payload = genesis_payload(
+                &mut rng,
+                &StreamType::Model,
+                &controller,
+                None,
+                false,
+                Ipld::Map(content),
+            )?;
+            let (cid, car) = signed_commit_car(&keypair, &controller, &payload).await?;
+            let stream_id = StreamId {
+                r#type: StreamIdType::Model,
+                cid,
+            };
+            log::info!("stream ID: {stream_id}");
+            stdout.write_all(&car).await?;
+        }
+        Operation::DataCommitCreate(args) => {
+            let keypair = parse_private_key(&args.key)?;
+            let controller = args
+                .controller
+                .unwrap_or_else(|| did_key(&keypair.public()));
+            let patch: Ipld = serde_ipld_dagjson::from_slice(args.patch.as_bytes())
+                .context("failed to parse patch as dag-json")?;
+            validate_patch(&patch)?;
+            let genesis = Cid::from_str(&args.genesis)
+                .with_context(|| format!("failed to parse genesis CID '{}'", args.genesis))?;
+            let prev = Cid::from_str(&args.prev)
+                .with_context(|| format!("failed to parse prev CID '{}'", args.prev))?;
+            let payload = Ipld::Map(BTreeMap::from([
+                ("id".to_string(), Ipld::Link(genesis)),
+                ("prev".to_string(), Ipld::Link(prev)),
+                ("header".to_string(), Ipld::Map(BTreeMap::new())),
+                ("data".to_string(), patch),
+            ]));
+            let (cid, car) = signed_commit_car(&keypair, &controller, &payload).await?;
+            log::info!("commit CID: {cid}");
+            stdout.write_all(&car).await?;
+        }
     };
     Ok(())
 }
@@ -151,17 +669,20 @@ fn convert_type(value: StreamType) -> StreamIdType {
         StreamType::Document => StreamIdType::ModelInstanceDocument,
     }
 }
-fn convert_network(value: Network, local_id: Option<u32>) -> ceramic_core::Network {
-    match value {
+fn convert_network(value: Network, local_id: Option<u32>) -> Result<ceramic_core::Network> {
+    Ok(match value {
         Network::Mainnet => ceramic_core::Network::Mainnet,
         Network::TestnetClay => ceramic_core::Network::TestnetClay,
         Network::DevUnstable => ceramic_core::Network::DevUnstable,
-        Network::Local => ceramic_core::Network::Local(local_id.unwrap()),
+        Network::Local(id) => ceramic_core::Network::Local(id.or(local_id).ok_or_else(|| {
+            anyhow!("local network requires an ID, i.e. local:<id> or --local-network-id")
+        })?),
         Network::InMemory => ceramic_core::Network::InMemory,
-    }
+    })
 }
 
 fn random_event_id(
+    rng: &mut impl Rng,
     network: &ceramic_core::Network,
     sort_key: Option<String>,
     sort_value: Option<Vec<u8>>,
@@ -169,8 +690,7 @@ fn random_event_id(
     init_id: Option<String>,
 ) -> Result<EventId> {
     let sort_key = sort_key.unwrap_or_else(|| {
-        thread_rng()
-            .sample_iter(&Alphanumeric)
+        rng.sample_iter(&Alphanumeric)
             .take(12)
             .map(char::from)
             .collect()
@@ -178,23 +698,24 @@ fn random_event_id(
     let sort_value = sort_value.unwrap_or_else(|| {
         StreamId {
             r#type: StreamIdType::Model,
-            cid: random_cid(),
+            cid: random_cid(rng),
         }
         .to_vec()
     });
     let controller = controller.unwrap_or_else(|| {
-        thread_rng()
-            .sample_iter(&Alphanumeric)
+        rng.sample_iter(&Alphanumeric)
             .take(32)
             .map(char::from)
             .collect()
     });
     let init_id = init_id
-        .map(|id| StreamId::from_str(&id))
+        .map(|id| {
+            StreamId::from_str(&id).with_context(|| format!("failed to parse init ID '{id}'"))
+        })
         .transpose()?
         .unwrap_or_else(|| StreamId {
             r#type: StreamIdType::Model,
-            cid: random_cid(),
+            cid: random_cid(rng),
         });
     Ok(EventId::new(
         network,
@@ -202,6 +723,658 @@ fn random_event_id(
         &sort_value,
         &controller,
         &init_id.cid,
-        &random_cid(),
+        &random_cid(rng),
     ))
 }
+
+fn block_cid(codec: u64, data: &[u8]) -> Cid {
+    Cid::new_v1(codec, Code::Sha2_256.digest(data))
+}
+
+/// Components of a stream ID.
+#[derive(Serialize)]
+struct StreamIdInfo {
+    stream_id: String,
+    r#type: String,
+    cid: String,
+}
+
+/// Components of an event ID, each is None when the event ID is too short to contain it.
+#[derive(Serialize)]
+struct EventIdInfo {
+    network_id: Option<u64>,
+    separator: Option<String>,
+    controller: Option<String>,
+    init_id: Option<String>,
+    event_cid: Option<String>,
+}
+
+/// A network and the prefix it contributes to event IDs.
+#[derive(Serialize)]
+struct NetworkInfo {
+    network: String,
+    network_id: u64,
+    local_id: Option<u32>,
+    event_id_prefix: String,
+}
+
+/// Components of an interest, contains is only set when an event ID was checked.
+#[derive(Serialize)]
+struct InterestInfo {
+    sort_key_hash: String,
+    peer_id: String,
+    range: InterestRange,
+    not_after: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    contains: Option<bool>,
+}
+
+/// Event ID range of an interest, encoded as base16 multibase strings.
+#[derive(Serialize)]
+struct InterestRange {
+    start: String,
+    end: String,
+}
+
+/// A generated ed25519 keypair and the identities derived from it.
+#[derive(Serialize)]
+struct KeypairInfo {
+    private_key: String,
+    public_key: String,
+    did: String,
+    peer_id: String,
+}
+
+/// Summary of an event read from a CAR file.
+#[derive(Serialize)]
+struct EventSummary {
+    cid: String,
+    r#type: &'static str,
+    signed: bool,
+    signer: Option<String>,
+    cacao: Option<CacaoSummary>,
+    init_id: Option<String>,
+    prev: Option<String>,
+    header: Option<serde_json::Value>,
+    data: Option<serde_json::Value>,
+    proof: Option<String>,
+    path: Option<String>,
+}
+
+/// Issuer and validity of the CACAO capability used to sign an event.
+#[derive(Serialize)]
+struct CacaoSummary {
+    cid: String,
+    issuer: Option<String>,
+    domain: Option<String>,
+    expiration: Option<String>,
+}
+
+impl EventSummary {
+    fn new(root: Cid, blocks: &HashMap<Cid, Vec<u8>>) -> Result<Self> {
+        let block = blocks
+            .get(&root)
+            .ok_or_else(|| anyhow!("CAR file is missing block {root}"))?;
+        let (signed, signer, cacao, payload_cid) =
+            if root.codec() == <DagJoseCodec as Codec<Ipld>>::CODE {
+                let jws = Jws::from_dag_jose(block)?;
+                let header: Ipld = serde_ipld_dagjson::from_slice(&jws.protected)
+                    .context("failed to decode JWS protected header")?;
+                let signer = match header.get("kid")? {
+                    Some(Ipld::String(kid)) => kid.split('#').next().map(str::to_string),
+                    _ => None,
+                };
+                let cacao = match header.get("cap")? {
+                    Some(Ipld::String(cap)) => {
+                        let cid = cap.strip_prefix("ipfs://").unwrap_or(cap);
+                        let cid = Cid::from_str(cid)
+                            .with_context(|| format!("failed to parse CACAO CID '{cap}'"))?;
+                        Some(CacaoSummary::new(cid, blocks)?)
+                    }
+                    _ => None,
+                };
+                let payload =
+                    Cid::try_from(jws.payload.as_slice()).context("JWS payload is not a CID")?;
+                (true, signer, cacao, payload)
+            } else {
+                (false, None, None, root)
+            };
+        let payload = commit_payload(payload_cid, blocks)?;
+        let field = |name: &str| payload.get(name).ok().flatten();
+        let link = |name: &str| match field(name) {
+            Some(Ipld::Link(cid)) => Some(cid.to_string()),
+            _ => None,
+        };
+        let json = |name: &str| {
+            field(name)
+                .map(|value| -> Result<serde_json::Value> {
+                    Ok(serde_json::from_slice(&serde_ipld_dagjson::to_vec(value)?)?)
+                })
+                .transpose()
+        };
+        let r#type = if field("proof").is_some() {
+            "time"
+        } else if field("prev").is_some() {
+            "data"
+        } else {
+            "genesis"
+        };
+        Ok(Self {
+            cid: root.to_string(),
+            r#type,
+            signed,
+            signer,
+            cacao,
+            init_id: link("id"),
+            prev: link("prev"),
+            header: json("header")?,
+            data: json("data")?,
+            proof: link("proof"),
+            path: match field("path") {
+                Some(Ipld::String(path)) => Some(path.clone()),
+                _ => None,
+            },
+        })
+    }
+
+    fn to_text(&self) -> String {
+        let mut lines = vec![
+            format!("Event CID: {}", self.cid),
+            format!("Type: {}", self.r#type),
+            format!("Signed: {}", self.signed),
+        ];
+        let mut push = |label: &str, value: &Option<String>| {
+            if let Some(value) = value {
+                lines.push(format!("{label}: {value}"));
+            }
+        };
+        push("Signer", &self.signer);
+        if let Some(cacao) = &self.cacao {
+            push("CACAO", &Some(cacao.cid.clone()));
+            push("CACAO Issuer", &cacao.issuer);
+            push("CACAO Domain", &cacao.domain);
+            push("CACAO Expiration", &cacao.expiration);
+        }
+        push("Init ID", &self.init_id);
+        push("Prev", &self.prev);
+        push("Proof", &self.proof);
+        push("Path", &self.path);
+        push(
+            "Header",
+            &self.header.as_ref().map(|header| header.to_string()),
+        );
+        push("Data", &self.data.as_ref().map(|data| data.to_string()));
+        lines.join("\n")
+    }
+}
+
+impl CacaoSummary {
+    fn new(cid: Cid, blocks: &HashMap<Cid, Vec<u8>>) -> Result<Self> {
+        let payload = match blocks.get(&cid) {
+            Some(block) => Some(
+                serde_ipld_dagcbor::from_slice::<Ipld>(block)
+                    .with_context(|| format!("failed to decode CACAO {cid}"))?,
+            ),
+            // The CACAO is not required to be included with the event.
+            None => None,
+        };
+        let field = |name: &str| match payload
+            .as_ref()
+            .and_then(|cacao| cacao.get("p").ok().flatten())
+            .and_then(|p| p.get(name).ok().flatten())
+        {
+            Some(Ipld::String(value)) => Some(value.clone()),
+            _ => None,
+        };
+        Ok(Self {
+            cid: cid.to_string(),
+            issuer: field("iss"),
+            domain: field("domain"),
+            expiration: field("exp"),
+        })
+    }
+}
+
+/// Build the payload of a genesis commit for a stream of the given type.
+/// Documents get a random unique value in the header when `unique` is set.
+fn genesis_payload(
+    rng: &mut impl Rng,
+    r#type: &StreamType,
+    controller: &str,
+    model: Option<String>,
+    unique: bool,
+    data: Ipld,
+) -> Result<Ipld> {
+    let mut header = BTreeMap::from([
+        (
+            "controllers".to_string(),
+            Ipld::List(vec![Ipld::String(controller.to_string())]),
+        ),
+        ("sep".to_string(), Ipld::String("model".to_string())),
+    ]);
+    match r#type {
+        StreamType::Model => {
+            let (_base, model) = multibase::decode(METAMODEL_STREAM_ID)?;
+            header.insert("model".to_string(), Ipld::Bytes(model));
+        }
+        StreamType::Document => {
+            let model = model.ok_or_else(|| anyhow!("--model is required for document streams"))?;
+            header.insert(
+                "model".to_string(),
+                Ipld::Bytes(StreamId::from_str(&model)?.to_vec()),
+            );
+            if unique {
+                let mut unique = [0u8; 12];
+                rng.fill(&mut unique);
+                header.insert("unique".to_string(), Ipld::Bytes(unique.to_vec()));
+            }
+        }
+    };
+    Ok(Ipld::Map(BTreeMap::from([
+        ("header".to_string(), Ipld::Map(header)),
+        ("data".to_string(), data),
+    ])))
+}
+
+/// Validate that the data is a JSON Schema describing an object, as required for model documents.
+/// The schema is checked against the meta-schema of its $schema draft, 2020-12 when not set.
+fn validate_schema(schema: &Ipld) -> Result<()> {
+    let Ipld::Map(fields) = schema else {
+        bail!("schema must be a JSON object");
+    };
+    match fields.get("type") {
+        Some(Ipld::String(t)) if t == "object" => {}
+        _ => bail!("schema must have type \"object\""),
+    }
+    let json: serde_json::Value = serde_json::from_slice(&serde_ipld_dagjson::to_vec(schema)?)?;
+    jsonschema::meta::validate(&json)
+        .map_err(|err| anyhow!("schema is not a valid JSON Schema: {err}"))
+}
+
+/// Validate that the data is a JSON Patch array of operations.
+fn validate_patch(patch: &Ipld) -> Result<()> {
+    const OPS: [&str; 6] = ["add", "remove", "replace", "move", "copy", "test"];
+    let Ipld::List(operations) = patch else {
+        bail!("patch must be a JSON array of operations");
+    };
+    for operation in operations {
+        match (operation.get("op"), operation.get("path")) {
+            (Ok(Some(Ipld::String(op))), Ok(Some(Ipld::String(_))))
+                if OPS.contains(&op.as_str()) => {}
+            _ => bail!("invalid patch operation: {operation:?}"),
+        }
+    }
+    Ok(())
+}
+
+/// Decode the payload of a commit, following the link from the JWS of signed commits.
+fn commit_payload(cid: Cid, blocks: &HashMap<Cid, Vec<u8>>) -> Result<Ipld> {
+    let block = blocks
+        .get(&cid)
+        .ok_or_else(|| anyhow!("CAR file is missing block {cid}"))?;
+    if cid.codec() == <DagJoseCodec as Codec<Ipld>>::CODE {
+        let jws = Jws::from_dag_jose(block)?;
+        commit_payload(Cid::try_from(jws.payload.as_slice())?, blocks)
+    } else {
+        Ok(serde_ipld_dagcbor::from_slice(block)?)
+    }
+}
+
+/// Sign the payload and build a CAR file containing the signed commit and its payload.
+/// Returns the CID of the signed commit, which is also the root of the CAR file.
+async fn signed_commit_car(
+    keypair: &ed25519::Keypair,
+    controller: &str,
+    payload: &Ipld,
+) -> Result<(Cid, Vec<u8>)> {
+    let payload_block = serde_ipld_dagcbor::to_vec(payload)?;
+    let payload_cid = block_cid(<DagCborCodec as Codec<Ipld>>::CODE, &payload_block);
+    signed_car(keypair, controller, payload_cid, payload_block).await
+}
+
+/// Sign the CID of the payload block and build a CAR file containing the DAG-JOSE JWS and the
+/// payload block. Returns the CID of the JWS, which is also the root of the CAR file.
+async fn signed_car(
+    keypair: &ed25519::Keypair,
+    controller: &str,
+    payload_cid: Cid,
+    payload_block: Vec<u8>,
+) -> Result<(Cid, Vec<u8>)> {
+    let jose_block = Jws::sign(keypair, controller, &payload_cid)?.to_dag_jose()?;
+    let jose_cid = block_cid(<DagJoseCodec as Codec<Ipld>>::CODE, &jose_block);
+
+    let mut car = Vec::new();
+    let mut writer = CarWriter::new(CarHeader::V1(vec![jose_cid].into()), &mut car);
+    writer.write(jose_cid, jose_block).await?;
+    writer.write(payload_cid, payload_block).await?;
+    writer.finish().await?;
+    Ok((jose_cid, car))
+}
+
+/// Encode an Ethereum address with its EIP-55 mixed case checksum.
+fn checksum_address(address: &[u8]) -> String {
+    let lower = hex::encode(address);
+    let hash = Code::Keccak256.digest(lower.as_bytes());
+    let checksummed: String = lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let shift = if i % 2 == 0 { 4 } else { 0 };
+            if (hash.digest()[i / 2] >> shift) & 0x0f >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("0x{checksummed}")
+}
+
+/// Parse a hex encoded ed25519 private key.
+fn parse_private_key(key: &str) -> Result<ed25519::Keypair> {
+    // The key is secret so it is not included in the error messages.
+    let bytes = hex::decode(key.trim()).context("failed to decode hex private key")?;
+    Ok(ed25519::SecretKey::try_from_bytes(bytes)
+        .context("failed to parse ed25519 private key")?
+        .into())
+}
+
+/// Encode an ed25519 public key as a did:key.
+fn did_key(public: &ed25519::PublicKey) -> String {
+    let mut bytes = ED25519_PUB_PREFIX.to_vec();
+    bytes.extend_from_slice(&public.to_bytes());
+    format!("did:key:{}", multibase::encode(Base::Base58Btc, bytes))
+}
+
+/// Decode the ed25519 public key embedded in a did:key.
+fn did_key_public_key(did: &str) -> Result<ed25519::PublicKey> {
+    let key = did
+        .strip_prefix("did:key:")
+        .ok_or_else(|| anyhow!("{did} is not a did:key"))?;
+    let (_base, bytes) =
+        multibase::decode(key).with_context(|| format!("failed to decode did:key '{did}'"))?;
+    let public = bytes
+        .strip_prefix(&ED25519_PUB_PREFIX)
+        .ok_or_else(|| anyhow!("{did} is not an ed25519 did:key"))?;
+    ed25519::PublicKey::try_from_bytes(public)
+        .with_context(|| format!("failed to parse public key of did:key '{did}'"))
+}
+
+/// Key ID used in the protected header of a JWS signed by the controller.
+fn key_id(controller: &str) -> String {
+    match controller.strip_prefix("did:key:") {
+        Some(fragment) => format!("{controller}#{fragment}"),
+        None => controller.to_string(),
+    }
+}
+
+/// A JWS over the CID of a payload block, as used by signed Ceramic events.
+struct Jws {
+    protected: Vec<u8>,
+    payload: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+impl Jws {
+    fn sign(keypair: &ed25519::Keypair, controller: &str, payload: &Cid) -> Result<Self> {
+        let header = Ipld::Map(BTreeMap::from([
+            ("alg".to_string(), Ipld::String("EdDSA".to_string())),
+            ("kid".to_string(), Ipld::String(key_id(controller))),
+        ]));
+        let mut jws = Self {
+            protected: serde_ipld_dagjson::to_vec(&header)?,
+            payload: payload.to_bytes(),
+            signature: Vec::new(),
+        };
+        jws.signature = keypair.sign(jws.signing_input().as_bytes());
+        Ok(jws)
+    }
+
+    /// Check the key ID and expiry in the protected header, then return whether the signature is
+    /// valid for the public key. `now` is the current Unix time in seconds.
+    fn verify(&self, did: &str, public_key: &ed25519::PublicKey, now: u64) -> Result<bool> {
+        let header: Ipld = serde_ipld_dagjson::from_slice(&self.protected)?;
+        if let Some(Ipld::String(kid)) = header.get("kid")? {
+            let signer = kid.split('#').next().unwrap_or_default();
+            if signer != did {
+                bail!("key mismatch: JWS was signed by {signer} not {did}");
+            }
+        }
+        if let Some(Ipld::Integer(exp)) = header.get("exp")? {
+            if *exp < now as i128 {
+                bail!("JWS expired at {exp}");
+            }
+        }
+        Ok(public_key.verify(self.signing_input().as_bytes(), &self.signature))
+    }
+
+    fn signing_input(&self) -> String {
+        format!(
+            "{}.{}",
+            Base::Base64Url.encode(&self.protected),
+            Base::Base64Url.encode(&self.payload)
+        )
+    }
+
+    fn from_compact(jws: &str) -> Result<Self> {
+        let mut parts = jws.split('.').map(|part| Base::Base64Url.decode(part));
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(protected), Some(payload), Some(signature), None) => Ok(Self {
+                protected: protected?,
+                payload: payload?,
+                signature: signature?,
+            }),
+            _ => bail!("compact JWS must have exactly three parts"),
+        }
+    }
+
+    fn from_dag_jose(data: &[u8]) -> Result<Self> {
+        let jose: Ipld = serde_ipld_dagcbor::from_slice(data)?;
+        let bytes = |value: Option<&Ipld>, name: &str| match value {
+            Some(Ipld::Bytes(bytes)) => Ok(bytes.clone()),
+            _ => Err(anyhow!("DAG-JOSE data is missing {name}")),
+        };
+        let signature = jose
+            .get("signatures")?
+            .and_then(|s| s.get(0usize).ok().flatten());
+        Ok(Self {
+            payload: bytes(jose.get("payload")?, "payload")?,
+            protected: bytes(
+                signature.and_then(|s| s.get("protected").ok().flatten()),
+                "protected",
+            )?,
+            signature: bytes(
+                signature.and_then(|s| s.get("signature").ok().flatten()),
+                "signature",
+            )?,
+        })
+    }
+
+    fn to_compact(&self) -> String {
+        format!(
+            "{}.{}",
+            self.signing_input(),
+            Base::Base64Url.encode(&self.signature)
+        )
+    }
+
+    fn to_dag_jose(&self) -> Result<Vec<u8>> {
+        let signature = Ipld::Map(BTreeMap::from([
+            ("protected".to_string(), Ipld::Bytes(self.protected.clone())),
+            ("signature".to_string(), Ipld::Bytes(self.signature.clone())),
+        ]));
+        let jose = Ipld::Map(BTreeMap::from([
+            ("payload".to_string(), Ipld::Bytes(self.payload.clone())),
+            ("signatures".to_string(), Ipld::List(vec![signature])),
+        ]));
+        Ok(serde_ipld_dagcbor::to_vec(&jose)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn keypair() -> ed25519::Keypair {
+        parse_private_key(&hex::encode([7u8; 32])).unwrap()
+    }
+
+    fn payload() -> Cid {
+        block_cid(RAW_CODEC, b"hello world")
+    }
+
+    fn assert_same(a: &Jws, b: &Jws) {
+        assert_eq!(a.protected, b.protected);
+        assert_eq!(a.payload, b.payload);
+        assert_eq!(a.signature, b.signature);
+    }
+
+    fn is_valid(keypair: &ed25519::Keypair, jws: &Jws) -> bool {
+        keypair
+            .public()
+            .verify(jws.signing_input().as_bytes(), &jws.signature)
+    }
+
+    #[test]
+    fn jws_compact_round_trip() {
+        let keypair = keypair();
+        let jws = Jws::sign(&keypair, &did_key(&keypair.public()), &payload()).unwrap();
+        let decoded = Jws::from_compact(&jws.to_compact()).unwrap();
+        assert_same(&jws, &decoded);
+        assert_eq!(decoded.payload, payload().to_bytes());
+        assert!(is_valid(&keypair, &decoded));
+    }
+
+    #[test]
+    fn jws_dag_jose_round_trip() {
+        let keypair = keypair();
+        let jws = Jws::sign(&keypair, &did_key(&keypair.public()), &payload()).unwrap();
+        let decoded = Jws::from_dag_jose(&jws.to_dag_jose().unwrap()).unwrap();
+        assert_same(&jws, &decoded);
+        assert!(is_valid(&keypair, &decoded));
+    }
+
+    #[test]
+    fn jws_tampered_signature() {
+        let keypair = keypair();
+        let mut jws = Jws::sign(&keypair, &did_key(&keypair.public()), &payload()).unwrap();
+        jws.signature[0] ^= 1;
+        let decoded = Jws::from_compact(&jws.to_compact()).unwrap();
+        assert!(!is_valid(&keypair, &decoded));
+    }
+
+    /// Sign the payload with the given protected header fields.
+    fn sign_with_header(keypair: &ed25519::Keypair, header: Vec<(&str, Ipld)>) -> Jws {
+        let header = Ipld::Map(
+            header
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        );
+        let mut jws = Jws {
+            protected: serde_ipld_dagjson::to_vec(&header).unwrap(),
+            payload: payload().to_bytes(),
+            signature: Vec::new(),
+        };
+        jws.signature = keypair.sign(jws.signing_input().as_bytes());
+        jws
+    }
+
+    #[test]
+    fn jws_verify_valid() {
+        let keypair = keypair();
+        let did = did_key(&keypair.public());
+        let jws = Jws::sign(&keypair, &did, &payload()).unwrap();
+        assert!(jws.verify(&did, &keypair.public(), 0).unwrap());
+    }
+
+    #[test]
+    fn jws_verify_wrong_kid() {
+        let keypair = keypair();
+        let did = did_key(&keypair.public());
+        let other = did_key(&parse_private_key(&hex::encode([8u8; 32])).unwrap().public());
+        let jws = Jws::sign(&keypair, &other, &payload()).unwrap();
+        let err = jws.verify(&did, &keypair.public(), 0).unwrap_err();
+        assert!(err.to_string().starts_with("key mismatch"), "{err}");
+    }
+
+    #[test]
+    fn jws_verify_expired() {
+        let keypair = keypair();
+        let did = did_key(&keypair.public());
+        let jws = sign_with_header(
+            &keypair,
+            vec![
+                ("alg", Ipld::String("EdDSA".to_string())),
+                ("kid", Ipld::String(key_id(&did))),
+                ("exp", Ipld::Integer(1_000)),
+            ],
+        );
+        assert!(jws.verify(&did, &keypair.public(), 999).unwrap());
+        let err = jws.verify(&did, &keypair.public(), 1_001).unwrap_err();
+        assert_eq!(err.to_string(), "JWS expired at 1000");
+    }
+
+    #[test]
+    fn jws_verify_modified_payload() {
+        let keypair = keypair();
+        let did = did_key(&keypair.public());
+        let mut jws = Jws::sign(&keypair, &did, &payload()).unwrap();
+        jws.payload = block_cid(RAW_CODEC, b"goodbye world").to_bytes();
+        assert!(!jws.verify(&did, &keypair.public(), 0).unwrap());
+    }
+
+    fn document_genesis(unique: bool) -> Ipld {
+        genesis_payload(
+            &mut StdRng::seed_from_u64(0),
+            &StreamType::Document,
+            "did:key:z6Mk",
+            Some(
+                StreamId {
+                    r#type: StreamIdType::Model,
+                    cid: payload(),
+                }
+                .to_string(),
+            ),
+            unique,
+            Ipld::Null,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn genesis_payload_unique() {
+        let header = |genesis: &Ipld| match genesis.get("header").unwrap() {
+            Some(Ipld::Map(header)) => header.clone(),
+            _ => panic!("genesis has no header"),
+        };
+        assert!(matches!(
+            header(&document_genesis(true)).get("unique"),
+            Some(Ipld::Bytes(unique)) if unique.len() == 12
+        ));
+        assert!(!header(&document_genesis(false)).contains_key("unique"));
+    }
+
+    #[tokio::test]
+    async fn jws_car_contains_payload() {
+        let keypair = keypair();
+        let payload_block = serde_ipld_dagcbor::to_vec(&Ipld::String("hello".into())).unwrap();
+        let payload_cid = block_cid(<DagCborCodec as Codec<Ipld>>::CODE, &payload_block);
+        let (jose_cid, car) = signed_car(
+            &keypair,
+            &did_key(&keypair.public()),
+            payload_cid,
+            payload_block.clone(),
+        )
+        .await
+        .unwrap();
+        let (roots, blocks) = read_car(car.as_slice()).await.unwrap();
+        assert_eq!(roots, vec![jose_cid]);
+        assert_eq!(blocks[&payload_cid], payload_block);
+        let jws = Jws::from_dag_jose(&blocks[&jose_cid]).unwrap();
+        assert_eq!(jws.payload, payload_cid.to_bytes());
+        assert!(is_valid(&keypair, &jws));
+    }
+}
@@ -1,7 +1,8 @@
-use std::str::FromStr;
+use std::{path::PathBuf, str::FromStr};
 
 use cid::Cid;
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use rand::{rngs::StdRng, SeedableRng};
 
 /// Convert to and from multibase encodings
 #[derive(Parser, Debug)]
@@ -9,12 +10,90 @@ use clap::{Args, Parser, Subcommand, ValueEnum};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Command,
+
+    /// Increase log verbosity, may be repeated, overrides RUST_LOG.
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Only log errors, overrides RUST_LOG.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Seed for all randomly generated values, the same seed produces the same output.
+    #[arg(long, global = true)]
+    pub seed: Option<u64>,
+
+    /// Output structured JSON instead of text for commands that support it.
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Read input from a file instead of stdin, use - for stdin.
+    #[arg(long, global = true)]
+    pub input: Option<PathBuf>,
+
+    /// Write output to a file instead of stdout, use - for stdout.
+    #[arg(short, long, global = true)]
+    pub output: Option<PathBuf>,
+
+    /// Colorize text output, auto colors only when writing to a terminal.
+    #[arg(long, global = true, default_value = "auto", value_enum)]
+    pub color: ColorChoice,
+}
+
+impl Cli {
+    /// Log filter selected by the verbosity flags, None when RUST_LOG should be used.
+    pub fn log_filter(&self) -> Option<&'static str> {
+        if self.quiet {
+            return Some("error");
+        }
+        match self.verbose {
+            0 => None,
+            1 => Some("info"),
+            2 => Some("debug"),
+            _ => Some("trace"),
+        }
+    }
+
+    /// Random number generator for the command, seeded from --seed when set.
+    pub fn rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    pub fn output_format(&self) -> OutputFormat {
+        if self.json {
+            OutputFormat::Json
+        } else {
+            OutputFormat::Text
+        }
+    }
+}
+
+/// When to colorize text output
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+/// Format of structured command output
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
 }
 
 #[derive(Subcommand, Debug, Clone)]
 pub enum Command {
     /// Generate shell completion scripts to stdout, i.e. for bash run: source <(cpk completion bash)
     Completion(CompletionArgs),
+    /// Generate man pages, to stdout for cpk or to a directory for cpk and each subcommand
+    Man(ManArgs),
+    /// Print the version, git commit, enabled features and target of this build
+    BuildInfo,
     // ---------------- Multibase Tools ----------------------------//
     /// Decode encoded input from stdin without the multibase prefix.
     BaseGuess,
@@ -54,7 +133,16 @@ pub enum Command {
     Base64Url,
 
     // ---------------- Multihash Tools ----------------------------//
-    MultihashInspect,
+    /// Inspect a multihash read from stdin
+    MultihashInspect(MultihashInspectArgs),
+    /// Hash stdin and output the multihash bytes
+    MultihashCreate(MultihashCreateArgs),
+    /// Verify a multihash matches the data read from stdin
+    MultihashVerify(MultihashVerifyArgs),
+    /// Wrap a hex encoded digest into a multihash
+    MultihashFromDigest(MultihashFromDigestArgs),
+    /// Create a CIDv1 from a multihash read from stdin as hex or a multibase string
+    MultihashToCid(MultihashToCidArgs),
 
     // ---------------- Ceramic Tools ----------------------------//
     /// Create a stream ID
@@ -63,59 +151,277 @@ pub enum Command {
     StreamIdInspect(StreamIdInspectArgs),
     /// Generate a random stream ID
     StreamIdGenerate(StreamIdGenerateArgs),
-    /// Construct a stream ID from raw bytes
-    StreamIdFromBytes,
+    /// Construct a stream ID from bytes
+    StreamIdFromBytes(StreamIdFromBytesArgs),
+    /// Derive the stream ID of a genesis commit CAR file read from stdin
+    StreamIdFromGenesis,
     /// Generate a random event ID
     EventIdGenerate(EventIdGenerateArgs),
+    /// Create an event ID from its components
+    EventIdCreate(EventIdCreateArgs),
     /// Inspect a multibase encoded event ID
     EventIdInspect(EventIdInspectArgs),
+    /// Inspect a network and the event ID prefix it produces
+    NetworkInspect(NetworkInspectArgs),
     /// Inspect an event car file
     EventInspect,
     /// Inspect a multibase encoded interest
     InterestInspect(InterestInspectArgs),
     /// Generate a random did:key method
     DidKeyGenerate,
+    /// Generate a did:pkh for an Ethereum address, uses a random address if not set
+    DidPkhGenerate(DidPkhGenerateArgs),
     /// Generate a random peer ID
     PeerIdGenerate,
+    /// Generate an ed25519 keypair with its did:key and peer ID
+    KeypairGenerate,
+    /// Derive the peer ID and did:key of an ed25519 key
+    PeerIdFromKey(PeerIdFromKeyArgs),
+    /// Sign a payload from stdin as a JWS
+    JwsSign(JwsSignArgs),
+    /// Verify a JWS from stdin against the signer DID
+    JwsVerify(JwsVerifyArgs),
+    /// Sign stdin with the ed25519 private key of a did:key, outputs a base64url signature
+    DidKeySign(DidKeySignArgs),
+    /// Verify a signature of stdin against the public key of a did:key
+    DidKeyVerify(DidKeyVerifyArgs),
+    /// Create a signed genesis commit, outputs a CAR file
+    GenesisCommitCreate(GenesisCommitCreateArgs),
+    /// Create a signed data commit, outputs a CAR file
+    DataCommitCreate(DataCommitCreateArgs),
+    /// Create a signed Model genesis commit from a JSON schema read from stdin, outputs a CAR file
+    ModelDefinitionCreate(ModelDefinitionCreateArgs),
 
     // ---------------- IPLD Tools ----------------------------//
     /// Generate a random stream ID
     CidGenerate,
     /// Output CID as raw bytes
-    CidAsBytes(CidInspectArgs),
+    CidAsBytes(CidAsBytesArgs),
     /// Inspect a CID
     CidInspect(CidInspectArgs),
+    /// Print a CID in every multibase encoding
+    CidBase(CidBaseArgs),
     /// Construct a CID from CID bytes
-    CidFromBytes,
+    CidFromBytes(CidFromBytesArgs),
     /// Hash bytes to compute a CID
     CidFromData(CidFromDataArgs),
+    /// Compute the CID of DAG-JSON data from stdin once encoded with the codec
+    IpldCid(IpldCidArgs),
     /// Convert DAG-JSON data to DAG-CBOR
-    DagJsonToCbor,
+    DagJsonToCbor(DagJsonToCborArgs),
     /// Convert DAG-CBOR data to DAG-JSON
-    DagCborToJson,
+    DagCborToJson(DagToJsonArgs),
     /// Convert DAG-JOSE data to DAG-JSON
-    DagJoseToJson,
+    DagJoseToJson(DagToJsonArgs),
     /// Inspect DAG-CBOR encoded data
     DagCborInspect,
     /// Index into DAG-CBOR encoded data
     DagCborIndex(DagCborIndexArgs),
+    /// Set the value at a path in DAG-CBOR encoded data
+    DagCborSet(DagCborSetArgs),
+    /// List the keys of a DAG-CBOR map, the length of a list or the type of a scalar
+    DagCborKeys,
     /// List contents of a CAR archive
     CarInspect(CarInspectArgs),
+    /// List the root CIDs of a CAR archive, only the header is read
+    CarRoots,
     /// Extract a single root CID from a CAR archive
     CarExtract(CarExtractArgs),
     /// Construct a CAR file bytes from a list of blocks
     CarFromBlocks(CarFromBlocksArgs),
+    /// Print the link graph of the DAG in a CAR archive as a tree
+    IpldTraverse(IpldTraverseArgs),
+    /// Validate that a CAR archive contains its roots and every block linked from them, and that
+    /// each block matches the hash of its CID
+    CarValidate,
 
     // ---------------- Libp2p Tools ----------------------------//
     P2pPing(PingArgs),
     P2pIdentify(IdentifyArgs),
+    /// Find the addresses of a peer using the Kademlia DHT
+    P2pFindPeer(FindPeerArgs),
+    /// Find the peers providing a block using the Kademlia DHT
+    P2pDhtProviders(DhtProvidersArgs),
+    /// Subscribe to a gossipsub topic and print received messages
+    P2pGossipsubListen(GossipsubListenArgs),
+    /// Listen for inbound connections and respond to ping and identify requests
+    P2pListen(ListenArgs),
+    /// Discover peers on the local network using mDNS
+    P2pDiscover(DiscoverArgs),
+    /// Identify and ping a peer over a single connection
+    P2pProbe(ProbeArgs),
+    /// Measure the time to establish connections to a peer
+    P2pConnectTime(ConnectTimeArgs),
+    /// Fetch a block from a peer using bitswap, outputs the raw block bytes
+    P2pGetBlock(GetBlockArgs),
+    /// Ask an AutoNAT server whether the local node is publicly reachable
+    P2pAutonat(AutonatArgs),
+}
+
+impl Command {
+    /// Swarm options of the p2p commands.
+    pub fn swarm_args(&self) -> Option<&SwarmArgs> {
+        match self {
+            Command::P2pPing(args) => Some(&args.swarm),
+            Command::P2pIdentify(args) => Some(&args.swarm),
+            Command::P2pFindPeer(args) => Some(&args.swarm),
+            Command::P2pDhtProviders(args) => Some(&args.swarm),
+            Command::P2pGossipsubListen(args) => Some(&args.swarm),
+            Command::P2pListen(args) => Some(&args.swarm),
+            Command::P2pDiscover(args) => Some(&args.swarm),
+            Command::P2pProbe(args) => Some(&args.swarm),
+            Command::P2pConnectTime(args) => Some(&args.swarm),
+            Command::P2pGetBlock(args) => Some(&args.swarm),
+            Command::P2pAutonat(args) => Some(&args.swarm),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Args, Debug, Clone)]
 pub struct CompletionArgs {
     /// Shell type.
+    #[arg(value_enum, required_unless_present = "all_shells")]
+    pub shell: Option<clap_complete_command::Shell>,
+
+    /// Directory where completion scripts are written as cpk.<shell> instead of stdout.
+    #[arg(long)]
+    pub output_dir: Option<PathBuf>,
+
+    /// Generate completion scripts for all supported shells, requires --output-dir.
+    #[arg(long, requires = "output_dir", conflicts_with = "shell")]
+    pub all_shells: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ManArgs {
+    /// Directory where a man page for cpk and each subcommand is written.
+    #[arg(long)]
+    pub output_dir: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct MultihashInspectArgs {
+    /// Encoding of the multihash read from stdin
+    #[arg(long, default_value = "raw", value_enum)]
+    pub encoding: InputEncoding,
+    /// Read one multihash per line and output a table row for each, requires a text encoding.
+    #[arg(long, default_value_t = false)]
+    pub lines: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct MultihashCreateArgs {
+    /// Hash function
     #[arg(value_enum)]
-    pub shell: clap_complete_command::Shell,
+    pub code: HashCode,
+    /// Output the multihash as hex instead of raw bytes
+    #[arg(long, default_value_t = false)]
+    pub hex: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct MultihashVerifyArgs {
+    /// Multihash encoded as hex or a multibase string
+    #[arg()]
+    pub multihash: String,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct MultihashFromDigestArgs {
+    /// Hash function name, i.e. sha2-256, or its multicodec code as decimal or 0x prefixed hex
+    #[arg()]
+    pub code: String,
+    /// Hex encoded digest
+    #[arg()]
+    pub digest_hex: String,
+    /// Output the multihash as hex instead of raw bytes
+    #[arg(long, default_value_t = false)]
+    pub hex: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct MultihashToCidArgs {
+    /// Codec
+    #[arg()]
+    pub codec: u64,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum HashCode {
+    #[value(name = "identity")]
+    Identity,
+    #[value(name = "sha2-256")]
+    Sha2_256,
+    #[value(name = "sha2-512")]
+    Sha2_512,
+    #[value(name = "sha3-224")]
+    Sha3_224,
+    #[value(name = "sha3-256")]
+    Sha3_256,
+    #[value(name = "sha3-384")]
+    Sha3_384,
+    #[value(name = "sha3-512")]
+    Sha3_512,
+    #[value(name = "keccak-256")]
+    Keccak256,
+    #[value(name = "blake3")]
+    Blake3_256,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum InputEncoding {
+    /// Raw bytes
+    Raw,
+    /// Hexadecimal string
+    Hex,
+    /// Base32 string without a multibase prefix
+    Base32,
+    /// Base58 bitcoin string without a multibase prefix
+    #[value(name = "base58btc", alias = "base58-btc")]
+    Base58Btc,
+    /// Base64 string without a multibase prefix, padding is optional
+    Base64,
+}
+
+#[cfg(any(
+    feature = "ceramic",
+    feature = "ipld",
+    feature = "multibase",
+    feature = "multihash"
+))]
+impl InputEncoding {
+    /// Decode input bytes according to the encoding.
+    pub fn decode(&self, data: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        use anyhow::Context;
+
+        let text = |data: Vec<u8>| -> anyhow::Result<String> {
+            Ok(String::from_utf8(data)
+                .context("encoded input is not valid UTF-8")?
+                .trim()
+                .to_string())
+        };
+        match self {
+            InputEncoding::Raw => Ok(data),
+            InputEncoding::Hex => hex::decode(text(data)?).context("failed to decode hex input"),
+            InputEncoding::Base32 => multibase::Base::Base32Lower
+                .decode(text(data)?.to_lowercase())
+                .context("failed to decode base32 input"),
+            InputEncoding::Base58Btc => multibase::Base::Base58Btc
+                .decode(text(data)?)
+                .context("failed to decode base58btc input"),
+            InputEncoding::Base64 => multibase::Base::Base64
+                .decode(text(data)?.trim_end_matches('='))
+                .context("failed to decode base64 input"),
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct StreamIdFromBytesArgs {
+    /// Encoding of the stream ID bytes read from stdin
+    #[arg(long, default_value = "raw", value_enum)]
+    pub encoding: InputEncoding,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -144,8 +450,8 @@ pub struct StreamIdGenerateArgs {
 
 #[derive(Args, Debug, Clone)]
 pub struct EventIdGenerateArgs {
-    /// Network
-    #[arg(long, default_value = "testnet-clay", value_enum)]
+    /// Network, one of mainnet, testnet-clay, dev-unstable, in-memory, local or local:<id>
+    #[arg(long, default_value = "testnet-clay")]
     pub network: Network,
     /// Local Network ID, only used when network is local. If not set a random ID is used.
     #[arg(long)]
@@ -162,12 +468,53 @@ pub struct EventIdGenerateArgs {
     /// Stream ID of init event, if not set generates random value.
     #[arg(long)]
     pub init_id: Option<String>,
+    /// Number of event IDs to generate, only the components that are not set vary.
+    #[arg(long, default_value_t = 1)]
+    pub count: usize,
 }
+#[derive(Args, Debug, Clone)]
+pub struct EventIdCreateArgs {
+    /// Network, one of mainnet, testnet-clay, dev-unstable, in-memory, local or local:<id>
+    #[arg(long)]
+    pub network: Network,
+    /// Local Network ID, only used when network is local.
+    #[arg(long)]
+    pub local_network_id: Option<u32>,
+    /// Sort Key
+    #[arg(long)]
+    pub sort_key: String,
+    /// Multibase encoded sort value
+    #[arg(long)]
+    pub sort_value: String,
+    /// Controller
+    #[arg(long)]
+    pub controller: String,
+    /// Stream ID of init event
+    #[arg(long)]
+    pub init_id: String,
+    /// CID of the event
+    #[arg(long)]
+    pub event_cid: String,
+}
+
 #[derive(Args, Debug, Clone)]
 pub struct EventIdInspectArgs {
     /// Multibase encoded Event ID to decode
     #[arg()]
     pub event_id: String,
+    /// Multibase used to encode the components of the event ID
+    #[arg(long, default_value = "base16", value_enum)]
+    pub value_base: BaseName,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct NetworkInspectArgs {
+    /// Network, one of mainnet, testnet-clay, dev-unstable, in-memory, local or local:<id>
+    #[arg()]
+    pub network: Network,
+    /// Local Network ID, required when network is local without an ID.
+    #[arg(long)]
+    pub local_network_id: Option<u32>,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -175,17 +522,242 @@ pub struct InterestInspectArgs {
     /// Hex encoded Interest to decode
     #[arg()]
     pub interest: String,
+    /// Multibase encoded event ID, reports whether it falls within the interest range.
+    #[arg(long)]
+    pub contains: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DidPkhGenerateArgs {
+    /// EIP-155 chain ID
+    #[arg(long, default_value_t = 1)]
+    pub chain_id: u64,
+    /// Hex encoded Ethereum address, if not set generates random value.
+    #[arg(long)]
+    pub address: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct PeerIdFromKeyArgs {
+    /// Hex encoded ed25519 private key or a did:key
+    #[arg()]
+    pub key: String,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct JwsSignArgs {
+    /// Hex encoded ed25519 private key used to sign.
+    #[arg(long, env = "NODE_PRIVATE_KEY", hide_env_values = true)]
+    pub key: String,
+    /// Controller DID, if not set uses the did:key of the private key.
+    #[arg(long)]
+    pub controller: Option<String>,
+    /// Encoding of the payload read from stdin.
+    #[arg(long, default_value = "dag-json", value_enum)]
+    pub payload: JwsPayload,
+    /// Output format of the JWS.
+    #[arg(long, default_value = "compact", value_enum)]
+    pub format: JwsFormat,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct JwsVerifyArgs {
+    /// DID of the signer, only ed25519 did:key DIDs are supported.
+    #[arg(long)]
+    pub did: String,
+    /// Format of the JWS read from stdin.
+    #[arg(long, default_value = "compact", value_enum)]
+    pub format: JwsFormat,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DidKeySignArgs {
+    /// The did:key of the signer, must match the private key.
+    #[arg(long)]
+    pub did: String,
+    /// Hex encoded ed25519 private key used to sign.
+    #[arg(long, env = "NODE_PRIVATE_KEY", hide_env_values = true)]
+    pub key: String,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DidKeyVerifyArgs {
+    /// The did:key of the signer, only ed25519 did:key DIDs are supported.
+    #[arg(long)]
+    pub did: String,
+    /// Base64url encoded signature.
+    #[arg(long)]
+    pub signature: String,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct GenesisCommitCreateArgs {
+    /// Stream type.
+    #[arg(long, value_enum)]
+    pub r#type: StreamType,
+    /// Controller DID, if not set uses the did:key of the private key.
+    #[arg(long)]
+    pub controller: Option<String>,
+    /// Model stream ID of the document, required when type is document.
+    #[arg(long)]
+    pub model: Option<String>,
+    /// Account relation of the model of the document. Only list documents get a random unique
+    /// value, so the single document of an account has a deterministic stream ID.
+    #[arg(long, default_value = "list", value_enum)]
+    pub account_relation: AccountRelation,
+    /// DAG-JSON content of the stream.
+    #[arg(long)]
+    pub content: Option<String>,
+    /// Hex encoded ed25519 private key used to sign.
+    #[arg(long, env = "NODE_PRIVATE_KEY", hide_env_values = true)]
+    pub key: String,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DataCommitCreateArgs {
+    /// CID of the genesis commit of the stream
+    #[arg(long)]
+    pub genesis: String,
+    /// CID of the previous commit of the stream
+    #[arg(long)]
+    pub prev: String,
+    /// JSON Patch array of operations to apply to the stream content.
+    #[arg(long)]
+    pub patch: String,
+    /// Controller DID, if not set uses the did:key of the private key.
+    #[arg(long)]
+    pub controller: Option<String>,
+    /// Hex encoded ed25519 private key used to sign.
+    #[arg(long, env = "NODE_PRIVATE_KEY", hide_env_values = true)]
+    pub key: String,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ModelDefinitionCreateArgs {
+    /// Name of the model
+    #[arg(long)]
+    pub name: String,
+    /// Description of the model
+    #[arg(long)]
+    pub description: Option<String>,
+    /// Relation of documents of the model to their controller account
+    #[arg(long, default_value = "list", value_enum)]
+    pub account_relation: AccountRelation,
+    /// Controller DID, if not set uses the did:key of the private key.
+    #[arg(long)]
+    pub controller: Option<String>,
+    /// Hex encoded ed25519 private key used to sign.
+    #[arg(long, env = "NODE_PRIVATE_KEY", hide_env_values = true)]
+    pub key: String,
 }
 
 #[derive(ValueEnum, Clone, Debug)]
+pub enum AccountRelation {
+    /// Accounts may create many documents of the model
+    List,
+    /// Accounts may create a single document of the model
+    Single,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum JwsPayload {
+    /// DAG-JSON data, signed as a DAG-CBOR block
+    DagJson,
+    /// Raw bytes, signed as a raw block
+    Raw,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum JwsFormat {
+    /// Compact serialization, i.e. header.payload.signature
+    Compact,
+    /// DAG-JOSE encoded bytes
+    DagJose,
+    /// CAR file with the DAG-JOSE JWS as root followed by the payload block
+    Car,
+}
+
+#[derive(Clone, Debug)]
 pub enum Network {
     Mainnet,
     TestnetClay,
     DevUnstable,
-    Local,
+    Local(Option<u32>),
     InMemory,
 }
 
+impl FromStr for Network {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Accept the canonical names used on the wire, i.e. /ceramic/testnet-clay
+        let name = match s {
+            "/ceramic" => "mainnet",
+            _ => s.trim_start_matches("/ceramic/"),
+        };
+        match name {
+            "mainnet" => Ok(Self::Mainnet),
+            "testnet-clay" => Ok(Self::TestnetClay),
+            "dev-unstable" => Ok(Self::DevUnstable),
+            "in-memory" | "inmemory" => Ok(Self::InMemory),
+            "local" => Ok(Self::Local(None)),
+            _ => {
+                let id = name
+                    .strip_prefix("local:")
+                    .or_else(|| name.strip_prefix("local-"))
+                    .ok_or_else(|| anyhow::anyhow!("unknown network: {s}"))?;
+                Ok(Self::Local(Some(id.parse()?)))
+            }
+        }
+    }
+}
+
+/// Multibase encodings, named as the multibase commands.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum BaseName {
+    Base2,
+    Base8,
+    Base10,
+    Base16,
+    Base16Upper,
+    Base32Hex,
+    Base32HexUpper,
+    Base32,
+    Base32Upper,
+    Base32Z,
+    Base36,
+    Base36Upper,
+    Base58Flickr,
+    #[value(alias = "base58btc")]
+    Base58Btc,
+    Base64,
+    Base64Url,
+}
+
+#[cfg(any(feature = "ceramic", feature = "ipld", feature = "multibase"))]
+impl From<BaseName> for multibase::Base {
+    fn from(value: BaseName) -> Self {
+        match value {
+            BaseName::Base2 => multibase::Base::Base2,
+            BaseName::Base8 => multibase::Base::Base8,
+            BaseName::Base10 => multibase::Base::Base10,
+            BaseName::Base16 => multibase::Base::Base16Lower,
+            BaseName::Base16Upper => multibase::Base::Base16Upper,
+            BaseName::Base32Hex => multibase::Base::Base32HexLower,
+            BaseName::Base32HexUpper => multibase::Base::Base32HexUpper,
+            BaseName::Base32 => multibase::Base::Base32Lower,
+            BaseName::Base32Upper => multibase::Base::Base32Upper,
+            BaseName::Base32Z => multibase::Base::Base32Z,
+            BaseName::Base36 => multibase::Base::Base36Lower,
+            BaseName::Base36Upper => multibase::Base::Base36Upper,
+            BaseName::Base58Flickr => multibase::Base::Base58Flickr,
+            BaseName::Base58Btc => multibase::Base::Base58Btc,
+            BaseName::Base64 => multibase::Base::Base64,
+            BaseName::Base64Url => multibase::Base::Base64Url,
+        }
+    }
+}
+
 #[derive(ValueEnum, Clone, Debug)]
 pub enum StreamType {
     Model,
@@ -197,6 +769,33 @@ pub struct CidInspectArgs {
     /// CID, if `-` will read CID from STDIN as a string.
     #[arg()]
     pub cid: String,
+    /// Multibase encoding used to render the CID
+    #[arg(long, default_value = "base32", value_enum)]
+    pub base: BaseName,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CidBaseArgs {
+    /// CID, if `-` will read CID from STDIN as a string.
+    #[arg()]
+    pub cid: String,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CidAsBytesArgs {
+    /// CID, if `-` will read CID from STDIN as a string.
+    #[arg()]
+    pub cid: String,
+    /// Output the bytes as a hex string instead of raw bytes.
+    #[arg(long, default_value_t = false)]
+    pub hex: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CidFromBytesArgs {
+    /// Encoding of the CID bytes read from stdin
+    #[arg(long, default_value = "raw", value_enum)]
+    pub encoding: InputEncoding,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -206,18 +805,46 @@ pub struct CidFromDataArgs {
     pub codec: u64,
 }
 
+#[derive(Args, Debug, Clone)]
+pub struct IpldCidArgs {
+    /// Codec used to encode the data, either dag-cbor (113) or dag-json (297)
+    #[arg()]
+    pub codec: u64,
+    /// Hash function
+    #[arg(long, default_value = "sha2-256", value_enum)]
+    pub hash: HashCode,
+}
+
 #[derive(Args, Debug, Clone)]
 pub struct CarInspectArgs {
     /// When true, only metadata about the car file is decoded
     #[arg(long, default_value_t = false)]
     pub metadata_only: bool,
+    /// Multibase encoding used to render CIDs
+    #[arg(long, default_value = "base32", value_enum)]
+    pub base: BaseName,
 }
 
 #[derive(Args, Debug, Clone)]
 pub struct CarExtractArgs {
     /// CID
-    #[arg()]
-    pub cid: String,
+    #[arg(required_unless_present = "cid_file", conflicts_with = "cid_file")]
+    pub cid: Option<String>,
+
+    /// File containing the CID to extract, useful in pipelines as stdin is the CAR file.
+    #[arg(long)]
+    pub cid_file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct IpldTraverseArgs {
+    /// CID of the node to start from, defaults to the roots of the CAR archive.
+    #[arg(long)]
+    pub root: Option<Cid>,
+
+    /// Maximum depth of links followed from the root.
+    #[arg(long, default_value_t = 64)]
+    pub max_depth: usize,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -254,6 +881,20 @@ impl FromStr for CarBlockValue {
     }
 }
 
+#[derive(Args, Debug, Clone)]
+pub struct DagJsonToCborArgs {
+    /// Treat each input line as a separate DAG-JSON value, writing one hex line per value
+    #[arg(long)]
+    pub ndjson: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DagToJsonArgs {
+    /// Indent the DAG-JSON output for reading
+    #[arg(long, default_value_t = false)]
+    pub pretty: bool,
+}
+
 #[derive(Args, Debug, Clone)]
 pub struct DagCborIndexArgs {
     /// Index path into the IPLD value
@@ -261,9 +902,20 @@ pub struct DagCborIndexArgs {
     pub index: String,
 }
 
+#[derive(Args, Debug, Clone)]
+pub struct DagCborSetArgs {
+    /// Path into the IPLD value, intermediate maps are created when missing
+    #[arg()]
+    pub path: String,
+
+    /// DAG-JSON value to set, i.e. '{"/":"<cid>"}' for a link
+    #[arg()]
+    pub value_json: String,
+}
+
 #[derive(Args, Debug, Clone)]
 pub struct PingArgs {
-    /// Multiaddr for Peer
+    /// Multiaddr for Peer, or a peer ID to resolve using the DHT through --bootstrap peers
     #[arg()]
     pub peer_addr: String,
 
@@ -278,10 +930,215 @@ pub struct PingArgs {
     /// Timeout in seconds to wait for a pong
     #[arg(short, long, default_value_t = 20)]
     pub timeout: u32,
+
+    /// Output format of the responses and summary, use the global --json flag for JSON
+    #[arg(long, default_value = "text", value_enum)]
+    pub format: PingFormat,
+
+    /// Multiaddr of a bootstrap peer including its /p2p/<peer id>, may be repeated.
+    /// Required when pinging a peer ID.
+    #[arg(short, long)]
+    pub bootstrap: Vec<String>,
+
+    /// Kademlia protocol name of the DHT used to resolve a peer ID
+    #[arg(long, default_value = "/ipfs/kad/1.0.0")]
+    pub dht_protocol: String,
+
+    /// Timeout in seconds to wait for the DHT query resolving a peer ID
+    #[arg(long, default_value_t = 60)]
+    pub dht_timeout: u32,
+
+    #[command(flatten)]
+    pub swarm: SwarmArgs,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum PingFormat {
+    /// Human readable responses followed by a summary
+    Text,
+    /// A single JSON object containing the responses and a summary, selected with the global
+    /// `--json` flag
+    #[value(skip)]
+    Json,
+    /// One `seq,peer,rtt_ms` row per response followed by the summary
+    Csv,
 }
 #[derive(Args, Debug, Clone)]
 pub struct IdentifyArgs {
     /// Multiaddr for Peer
     #[arg()]
     pub peer_addr: String,
+
+    /// Timeout in seconds to wait for the identify exchange
+    #[arg(short, long, default_value_t = 30)]
+    pub timeout: u32,
+
+    #[command(flatten)]
+    pub swarm: SwarmArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct FindPeerArgs {
+    /// Peer ID to find
+    #[arg()]
+    pub peer_id: String,
+
+    /// Multiaddr of a bootstrap peer including its /p2p/<peer id>, may be repeated.
+    #[arg(short, long, required = true)]
+    pub bootstrap: Vec<String>,
+
+    /// Kademlia protocol name of the DHT
+    #[arg(long, default_value = "/ipfs/kad/1.0.0")]
+    pub protocol: String,
+
+    /// Timeout in seconds to wait for the DHT query
+    #[arg(short, long, default_value_t = 60)]
+    pub timeout: u32,
+
+    #[command(flatten)]
+    pub swarm: SwarmArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DhtProvidersArgs {
+    /// CID of the block
+    #[arg()]
+    pub cid: Cid,
+
+    /// Multiaddr of a bootstrap peer including its /p2p/<peer id>, may be repeated.
+    #[arg(short, long, required = true)]
+    pub bootstrap: Vec<String>,
+
+    /// Kademlia protocol name of the DHT
+    #[arg(long, default_value = "/ipfs/kad/1.0.0")]
+    pub protocol: String,
+
+    /// Timeout in seconds to wait for the DHT query
+    #[arg(short, long, default_value_t = 60)]
+    pub timeout: u32,
+
+    #[command(flatten)]
+    pub swarm: SwarmArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct GossipsubListenArgs {
+    /// Multiaddr for Peer
+    #[arg()]
+    pub peer_addr: String,
+
+    /// Gossipsub topic to subscribe to
+    #[arg()]
+    pub topic: String,
+
+    #[command(flatten)]
+    pub swarm: SwarmArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ListenArgs {
+    /// Port to listen on, TCP and QUIC listeners share the port number.
+    #[arg(short, long, default_value_t = 4001)]
+    pub port: u16,
+
+    #[command(flatten)]
+    pub swarm: SwarmArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ProbeArgs {
+    /// Multiaddr for Peer
+    #[arg()]
+    pub peer_addr: String,
+
+    /// Number of pings to send after identifying the peer.
+    #[arg(short, long, default_value_t = 3)]
+    pub count: usize,
+
+    /// Interval in seconds between pings
+    #[arg(short, long, default_value_t = 1)]
+    pub interval: u32,
+
+    /// Timeout in seconds to wait for a pong
+    #[arg(short, long, default_value_t = 20)]
+    pub timeout: u32,
+
+    #[command(flatten)]
+    pub swarm: SwarmArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ConnectTimeArgs {
+    /// Multiaddr for Peer
+    #[arg()]
+    pub peer_addr: String,
+
+    /// Number of connections to establish and close
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub iterations: usize,
+
+    #[command(flatten)]
+    pub swarm: SwarmArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct GetBlockArgs {
+    /// Multiaddr for Peer
+    #[arg()]
+    pub peer_addr: String,
+
+    /// CID of the block
+    #[arg()]
+    pub cid: Cid,
+
+    /// Timeout in seconds to wait for the block
+    #[arg(short, long, default_value_t = 30)]
+    pub timeout: u32,
+
+    #[command(flatten)]
+    pub swarm: SwarmArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct AutonatArgs {
+    /// Multiaddr of the AutoNAT server including its /p2p/<peer id>
+    #[arg()]
+    pub server_addr: String,
+
+    /// Timeout in seconds to wait for the NAT status
+    #[arg(short, long, default_value_t = 60)]
+    pub timeout: u32,
+
+    #[command(flatten)]
+    pub swarm: SwarmArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DiscoverArgs {
+    #[command(flatten)]
+    pub swarm: SwarmArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SwarmArgs {
+    /// File containing a hex encoded ed25519 private key used as the local identity,
+    /// created with a new random key if it does not exist. Cannot be used with --seed.
+    #[arg(long)]
+    pub key_file: Option<PathBuf>,
+
+    /// Transport used to connect to peers, defaults to all transports.
+    #[arg(long, default_value = "all", value_enum)]
+    pub transport: Transport,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Transport {
+    /// TCP, QUIC and websocket transports with DNS resolution
+    All,
+    /// TCP with noise and yamux
+    Tcp,
+    /// QUIC v1
+    Quic,
+    /// Websocket over TCP
+    Ws,
 }
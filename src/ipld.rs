@@ -1,38 +1,60 @@
-use std::{io::Cursor, str::FromStr};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    io::Cursor,
+    str::FromStr,
+};
 
-use anyhow::{anyhow, bail, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use cid::Cid;
+use clap::ValueEnum;
 use dag_jose::DagJoseCodec;
 use futures::pin_mut;
 use ipld_core::{codec::Codec, ipld::Ipld};
 use iroh_car::{CarHeader, CarReader, CarWriter};
+use multihash::Multihash;
 use multihash_codetable::{Code, MultihashDigest};
+use rand::rngs::StdRng;
+use serde::Serialize;
 use serde_ipld_dagcbor::codec::DagCborCodec;
 use serde_ipld_dagjson::codec::DagJsonCodec;
-use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
 
 use crate::{
     cli::{
-        CarExtractArgs, CarFromBlocksArgs, CarInspectArgs, CidFromDataArgs, CidInspectArgs,
-        Command, DagCborIndexArgs,
+        BaseName, CarExtractArgs, CarFromBlocksArgs, CarInspectArgs, CidAsBytesArgs, CidBaseArgs,
+        CidFromBytesArgs, CidFromDataArgs, CidInspectArgs, Command, DagCborIndexArgs,
+        DagCborSetArgs, DagJsonToCborArgs, DagToJsonArgs, IpldCidArgs, IpldTraverseArgs,
+        OutputFormat,
     },
-    random_cid,
+    multihash::{convert_code, digest_reader},
+    random_cid, read_car,
+    style::Style,
+    RAW_CODEC,
 };
 
+const DAG_PB_CODEC: u64 = 0x70;
+
 pub enum Operation {
     CidGenerate,
-    CidAsBytes(CidInspectArgs),
+    CidAsBytes(CidAsBytesArgs),
     CidInspect(CidInspectArgs),
-    CidFromBytes,
+    CidBase(CidBaseArgs),
+    CidFromBytes(CidFromBytesArgs),
     CidFromData(CidFromDataArgs),
-    DagJsonToCbor,
-    DagCborToJson,
-    DagJoseToJson,
+    IpldCid(IpldCidArgs),
+    DagJsonToCbor(DagJsonToCborArgs),
+    DagCborToJson(DagToJsonArgs),
+    DagJoseToJson(DagToJsonArgs),
     DagCborInspect,
     DagCborIndex(DagCborIndexArgs),
+    DagCborSet(DagCborSetArgs),
+    DagCborKeys,
     CarInspect(CarInspectArgs),
+    CarRoots,
     CarExtract(CarExtractArgs),
     CarFromBlocks(CarFromBlocksArgs),
+    IpldTraverse(IpldTraverseArgs),
+    CarValidate,
 }
 
 impl TryFrom<Command> for Operation {
@@ -43,16 +65,23 @@ impl TryFrom<Command> for Operation {
             Command::CidGenerate => Ok(Operation::CidGenerate),
             Command::CidAsBytes(args) => Ok(Operation::CidAsBytes(args)),
             Command::CidInspect(args) => Ok(Operation::CidInspect(args)),
-            Command::CidFromBytes => Ok(Operation::CidFromBytes),
+            Command::CidBase(args) => Ok(Operation::CidBase(args)),
+            Command::CidFromBytes(args) => Ok(Operation::CidFromBytes(args)),
             Command::CidFromData(args) => Ok(Operation::CidFromData(args)),
-            Command::DagJsonToCbor => Ok(Operation::DagJsonToCbor),
-            Command::DagCborToJson => Ok(Operation::DagCborToJson),
-            Command::DagJoseToJson => Ok(Operation::DagJoseToJson),
+            Command::IpldCid(args) => Ok(Operation::IpldCid(args)),
+            Command::DagJsonToCbor(args) => Ok(Operation::DagJsonToCbor(args)),
+            Command::DagCborToJson(args) => Ok(Operation::DagCborToJson(args)),
+            Command::DagJoseToJson(args) => Ok(Operation::DagJoseToJson(args)),
             Command::DagCborInspect => Ok(Operation::DagCborInspect),
             Command::DagCborIndex(args) => Ok(Operation::DagCborIndex(args)),
+            Command::DagCborSet(args) => Ok(Operation::DagCborSet(args)),
+            Command::DagCborKeys => Ok(Operation::DagCborKeys),
             Command::CarInspect(args) => Ok(Operation::CarInspect(args)),
+            Command::CarRoots => Ok(Operation::CarRoots),
             Command::CarExtract(args) => Ok(Operation::CarExtract(args)),
             Command::CarFromBlocks(args) => Ok(Operation::CarFromBlocks(args)),
+            Command::IpldTraverse(args) => Ok(Operation::IpldTraverse(args)),
+            Command::CarValidate => Ok(Operation::CarValidate),
             _ => Err(value),
         }
     }
@@ -60,92 +89,182 @@ impl TryFrom<Command> for Operation {
 
 pub async fn run(
     op: Operation,
+    format: OutputFormat,
+    style: Style,
+    mut rng: StdRng,
     stdin: impl AsyncRead + Send,
     stdout: impl AsyncWrite,
 ) -> Result<()> {
     pin_mut!(stdin, stdout);
     match op {
         Operation::CidGenerate => {
-            let cid = random_cid();
+            let cid = random_cid(&mut rng);
             stdout.write_all(format!("{cid}\n").as_bytes()).await?;
         }
         Operation::CidAsBytes(args) => {
-            let cid = if args.cid == "-" {
-                let mut data = Vec::new();
-                stdin.read_to_end(&mut data).await?;
-                let cid_str = std::str::from_utf8(&data)?.trim();
-                Cid::from_str(cid_str)?
+            let cid = read_cid(&args.cid, &mut stdin).await?;
+            if args.hex {
+                stdout
+                    .write_all(format!("{}\n", hex::encode(cid.to_bytes())).as_bytes())
+                    .await?;
             } else {
-                Cid::from_str(&args.cid)?
-            };
-            stdout.write_all(&cid.to_bytes()).await?;
+                stdout.write_all(&cid.to_bytes()).await?;
+            }
         }
         Operation::CidInspect(args) => {
-            let cid = if args.cid == "-" {
-                let mut data = Vec::new();
-                stdin.read_to_end(&mut data).await?;
-                let cid_str = std::str::from_utf8(&data)?.trim();
-                Cid::from_str(cid_str)?
-            } else {
-                Cid::from_str(&args.cid)?
+            let base: multibase::Base = args.base.into();
+            let cid = read_cid(&args.cid, &mut stdin).await?;
+            let out = match format {
+                OutputFormat::Text => fmt_cid(&cid, base, style)?,
+                OutputFormat::Json => {
+                    format!("{}\n", serde_json::to_string(&CidInfo::new(&cid, base)?)?)
+                }
+            };
+            stdout.write_all(out.as_bytes()).await?;
+        }
+        Operation::CidBase(args) => {
+            // CIDv0 can only be encoded as base58btc, so use the equivalent CIDv1.
+            let cid = read_cid(&args.cid, &mut stdin).await?.into_v1()?;
+            let encoded = BaseName::value_variants()
+                .iter()
+                .map(|base| {
+                    let name = base
+                        .to_possible_value()
+                        .map(|value| value.get_name().to_string())
+                        .unwrap_or_else(|| format!("{base:?}").to_lowercase());
+                    Ok((name, cid.to_string_of_base((*base).into())?))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let out = match format {
+                OutputFormat::Text => encoded
+                    .iter()
+                    .map(|(name, cid)| format!("{} {cid}\n", style.label(format!("{name}:"))))
+                    .collect(),
+                OutputFormat::Json => format!(
+                    "{}\n",
+                    serde_json::to_string(&encoded.into_iter().collect::<BTreeMap<_, _>>())?
+                ),
             };
-            stdout.write_all(fmt_cid(&cid)?.as_bytes()).await?;
+            stdout.write_all(out.as_bytes()).await?;
         }
-        Operation::CidFromBytes => {
+        Operation::CidFromBytes(args) => {
             let mut data = Vec::new();
-            stdin.read_to_end(&mut data).await?;
-            let cid = Cid::read_bytes(Cursor::new(data))?;
+            stdin
+                .read_to_end(&mut data)
+                .await
+                .context("failed to read CID bytes from stdin")?;
+            let bytes = args.encoding.decode(data)?;
+            let cid = Cid::read_bytes(Cursor::new(bytes))
+                .context("failed to parse CID from decoded bytes")?;
             stdout.write_all(format!("{cid}\n").as_bytes()).await?;
         }
         Operation::CidFromData(args) => {
+            let hash = digest_reader(Code::Sha2_256, &mut stdin).await?;
+            let cid = Cid::new_v1(args.codec, hash);
+            stdout.write_all(format!("{cid}\n").as_bytes()).await?;
+        }
+        Operation::IpldCid(args) => {
             let mut data = Vec::new();
-            stdin.read_to_end(&mut data).await?;
-            let hash = Code::Sha2_256.digest(&data);
+            stdin
+                .read_to_end(&mut data)
+                .await
+                .context("failed to read dag-json from stdin")?;
+            let dag_data: Ipld =
+                serde_ipld_dagjson::from_slice(&data).context("failed to decode dag-json input")?;
+            let block = match args.codec {
+                <DagCborCodec as Codec<Ipld>>::CODE => serde_ipld_dagcbor::to_vec(&dag_data)?,
+                <DagJsonCodec as Codec<Ipld>>::CODE => serde_ipld_dagjson::to_vec(&dag_data)?,
+                codec => bail!("unsupported codec 0x{codec:x}, expected dag-cbor or dag-json"),
+            };
+            let hash = match convert_code(args.hash) {
+                Some(code) => code.digest(&block),
+                None => Multihash::<64>::wrap(0x00, &block)?,
+            };
             let cid = Cid::new_v1(args.codec, hash);
             stdout.write_all(format!("{cid}\n").as_bytes()).await?;
         }
-        Operation::DagJsonToCbor => {
+        Operation::DagJsonToCbor(args) if args.ndjson => {
+            let mut lines = BufReader::new(stdin).lines();
+            let mut number = 0;
+            while let Some(line) = lines
+                .next_line()
+                .await
+                .context("failed to read dag-json line from stdin")?
+            {
+                number += 1;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let dag_data: Ipld = serde_ipld_dagjson::from_slice(line.as_bytes())
+                    .with_context(|| format!("failed to decode dag-json on line {number}"))?;
+                let out = serde_ipld_dagcbor::to_vec(&dag_data)?;
+                stdout
+                    .write_all(format!("{}\n", hex::encode(out)).as_bytes())
+                    .await?;
+            }
+        }
+        Operation::DagJsonToCbor(_) => {
             let mut data = Vec::new();
-            stdin.read_to_end(&mut data).await?;
-            let dag_data: Ipld = serde_ipld_dagjson::from_slice(&data)?;
+            stdin
+                .read_to_end(&mut data)
+                .await
+                .context("failed to read dag-json from stdin")?;
+            let dag_data: Ipld =
+                serde_ipld_dagjson::from_slice(&data).context("failed to decode dag-json input")?;
             let out = serde_ipld_dagcbor::to_vec(&dag_data)?;
             stdout
                 .write_all(format!("{}\n", hex::encode(out)).as_bytes())
                 .await?;
         }
-        Operation::DagCborToJson => {
+        Operation::DagCborToJson(args) => {
             let mut data = Vec::new();
-            stdin.read_to_end(&mut data).await?;
-            let dag_data: Ipld = serde_ipld_dagcbor::from_slice(&data)?;
-            let out = serde_ipld_dagjson::to_vec(&dag_data)?;
+            stdin
+                .read_to_end(&mut data)
+                .await
+                .context("failed to read dag-cbor from stdin")?;
+            let dag_data: Ipld =
+                serde_ipld_dagcbor::from_slice(&data).context("failed to decode dag-cbor input")?;
+            let out = dag_json(&dag_data, args.pretty)?;
             stdout.write_all(&out).await?;
             stdout.write_all(b"\n").await?;
         }
-        Operation::DagJoseToJson => {
+        Operation::DagJoseToJson(args) => {
             let mut data = Vec::new();
-            stdin.read_to_end(&mut data).await?;
-            let dag_data: Ipld = DagJoseCodec::decode_from_slice(&data)?;
-            let out = serde_ipld_dagjson::to_vec(&dag_data)?;
+            stdin
+                .read_to_end(&mut data)
+                .await
+                .context("failed to read dag-jose from stdin")?;
+            let dag_data: Ipld = DagJoseCodec::decode_from_slice(&data)
+                .context("failed to decode dag-jose input")?;
+            let out = dag_json(&dag_data, args.pretty)?;
             stdout.write_all(&out).await?;
             stdout.write_all(b"\n").await?;
         }
         Operation::DagCborInspect => {
             let mut data = Vec::new();
-            stdin.read_to_end(&mut data).await?;
-            let dag_data: Ipld = serde_ipld_dagcbor::from_slice(&data)?;
-            stdout
-                .write_all(format!("{dag_data:#?}\n").as_bytes())
-                .await?;
+            stdin
+                .read_to_end(&mut data)
+                .await
+                .context("failed to read dag-cbor from stdin")?;
+            let dag_data: Ipld =
+                serde_ipld_dagcbor::from_slice(&data).context("failed to decode dag-cbor input")?;
+            let out = style.debug_links(&format!("{dag_data:#?}"));
+            stdout.write_all(format!("{out}\n").as_bytes()).await?;
         }
         Operation::DagCborIndex(args) => {
             let mut data = Vec::new();
-            stdin.read_to_end(&mut data).await?;
-            let dag_data: Ipld = serde_ipld_dagcbor::from_slice(&data)?;
+            stdin
+                .read_to_end(&mut data)
+                .await
+                .context("failed to read dag-cbor from stdin")?;
+            let dag_data: Ipld =
+                serde_ipld_dagcbor::from_slice(&data).context("failed to decode dag-cbor input")?;
             let mut idx_data = dag_data;
             for index in args.index.split('/') {
                 idx_data = idx_data
-                    .take(index)?
-                    .ok_or_else(|| anyhow!("no IPLD data exists at index"))?;
+                    .take(index)
+                    .with_context(|| format!("failed to index IPLD data at '{index}'"))?
+                    .ok_or_else(|| anyhow!("no IPLD data exists at index '{}'", args.index))?;
             }
             match idx_data {
                 // Write nothing for Null values
@@ -163,42 +282,163 @@ pub async fn run(
                 Ipld::Link(cid) => stdout.write_all(&cid.to_bytes()).await?,
             };
         }
+        Operation::DagCborSet(args) => {
+            let mut data = Vec::new();
+            stdin
+                .read_to_end(&mut data)
+                .await
+                .context("failed to read dag-cbor from stdin")?;
+            let mut dag_data: Ipld =
+                serde_ipld_dagcbor::from_slice(&data).context("failed to decode dag-cbor input")?;
+            let value: Ipld = serde_ipld_dagjson::from_slice(args.value_json.as_bytes())
+                .with_context(|| format!("failed to parse value '{}'", args.value_json))?;
+            let path: Vec<&str> = args.path.split('/').collect();
+            set_path(&mut dag_data, &path, value)
+                .with_context(|| format!("failed to set IPLD data at '{}'", args.path))?;
+            stdout
+                .write_all(&serde_ipld_dagcbor::to_vec(&dag_data)?)
+                .await?;
+        }
+        Operation::DagCborKeys => {
+            let mut data = Vec::new();
+            stdin
+                .read_to_end(&mut data)
+                .await
+                .context("failed to read dag-cbor from stdin")?;
+            let dag_data: Ipld =
+                serde_ipld_dagcbor::from_slice(&data).context("failed to decode dag-cbor input")?;
+            let out = match (dag_data, format) {
+                // Map keys are already sorted
+                (Ipld::Map(map), OutputFormat::Text) => {
+                    map.keys().map(|key| format!("{key}\n")).collect()
+                }
+                (Ipld::Map(map), OutputFormat::Json) => {
+                    format!(
+                        "{}\n",
+                        serde_json::to_string(&map.keys().collect::<Vec<_>>())?
+                    )
+                }
+                (Ipld::List(list), OutputFormat::Text) => format!("List length: {}\n", list.len()),
+                (Ipld::List(list), OutputFormat::Json) => {
+                    format!(
+                        "{}\n",
+                        serde_json::to_string(&ListInfo { length: list.len() })?
+                    )
+                }
+                (scalar, OutputFormat::Text) => format!("{}\n", ipld_kind(&scalar)),
+                (scalar, OutputFormat::Json) => {
+                    format!(
+                        "{}\n",
+                        serde_json::to_string(&ScalarInfo {
+                            r#type: ipld_kind(&scalar)
+                        })?
+                    )
+                }
+            };
+            stdout.write_all(out.as_bytes()).await?;
+        }
         Operation::CarInspect(args) => {
-            let mut reader = CarReader::new(stdin).await?;
+            let base: multibase::Base = args.base.into();
+            let mut reader = CarReader::new(stdin)
+                .await
+                .context("failed to read CAR file header")?;
             let roots: Vec<Cid> = reader.header().roots().to_vec();
-            while let Some((cid, data)) = reader.next_block().await? {
-                stdout.write_all(fmt_cid(&cid)?.as_bytes()).await?;
-                stdout
-                    .write_all(
-                        format!("Root: {}\nLength: {}\n", roots.contains(&cid), data.len())
-                            .as_bytes(),
-                    )
-                    .await?;
-                if !args.metadata_only {
-                    let dag_data: Option<Ipld> = match cid.codec() {
-                        <DagCborCodec as Codec<Ipld>>::CODE => {
-                            Some(serde_ipld_dagcbor::from_slice(&data)?)
-                        }
-                        <DagJsonCodec as Codec<Ipld>>::CODE => {
-                            Some(serde_ipld_dagjson::from_slice(&data)?)
+            while let Some((cid, data)) = reader
+                .next_block()
+                .await
+                .context("failed to read block from CAR file")?
+            {
+                let dag_data: Option<Ipld> = if args.metadata_only {
+                    None
+                } else {
+                    decode_block(&cid, &data)?
+                };
+                let dag_json = dag_data
+                    .map(|dag_data| serde_ipld_dagjson::to_vec(&dag_data))
+                    .transpose()?;
+                match format {
+                    OutputFormat::Text => {
+                        stdout
+                            .write_all(fmt_cid(&cid, base, style)?.as_bytes())
+                            .await?;
+                        stdout
+                            .write_all(
+                                format!(
+                                    "{} {}\n{} {}\n",
+                                    style.label("Root:"),
+                                    roots.contains(&cid),
+                                    style.label("Length:"),
+                                    data.len()
+                                )
+                                .as_bytes(),
+                            )
+                            .await?;
+                        if let Some(dag_json) = dag_json {
+                            stdout.write_all(&dag_json).await?;
+                            stdout.write_all(b"\n").await?;
                         }
-                        <DagJoseCodec as Codec<Ipld>>::CODE => {
-                            Some(DagJoseCodec::decode_from_slice(&data)?)
-                        }
-                        _ => None,
-                    };
-                    if let Some(dag_data) = dag_data {
-                        let out = serde_ipld_dagjson::to_vec(&dag_data)?;
-                        stdout.write_all(&out).await?;
-                        stdout.write_all(b"\n").await?;
+                    }
+                    OutputFormat::Json => {
+                        let block = CarBlockInfo {
+                            cid: CidInfo::new(&cid, base)?,
+                            root: roots.contains(&cid),
+                            length: data.len(),
+                            data: dag_json
+                                .map(|dag_json| serde_json::from_slice(&dag_json))
+                                .transpose()?,
+                        };
+                        stdout
+                            .write_all(format!("{}\n", serde_json::to_string(&block)?).as_bytes())
+                            .await?;
                     }
                 }
             }
         }
+        Operation::CarRoots => {
+            let reader = CarReader::new(stdin)
+                .await
+                .context("failed to read CAR file header")?;
+            let out: String = match format {
+                OutputFormat::Text => reader
+                    .header()
+                    .roots()
+                    .iter()
+                    .map(|root| format!("{root}\n"))
+                    .collect(),
+                OutputFormat::Json => format!(
+                    "{}\n",
+                    serde_json::to_string(
+                        &reader
+                            .header()
+                            .roots()
+                            .iter()
+                            .map(Cid::to_string)
+                            .collect::<Vec<_>>()
+                    )?
+                ),
+            };
+            stdout.write_all(out.as_bytes()).await?;
+        }
         Operation::CarExtract(args) => {
-            let find_cid = Cid::from_str(&args.cid)?;
-            let mut reader = CarReader::new(stdin).await?;
-            while let Some((cid, data)) = reader.next_block().await? {
+            let cid = match (args.cid, args.cid_file) {
+                (Some(cid), _) => cid,
+                (None, Some(path)) => tokio::fs::read_to_string(&path)
+                    .await
+                    .with_context(|| format!("failed to read CID file {}", path.display()))?
+                    .trim()
+                    .to_string(),
+                (None, None) => bail!("a CID or --cid-file is required"),
+            };
+            let find_cid =
+                Cid::from_str(&cid).with_context(|| format!("failed to parse CID '{cid}'"))?;
+            let mut reader = CarReader::new(stdin)
+                .await
+                .context("failed to read CAR file header")?;
+            while let Some((cid, data)) = reader
+                .next_block()
+                .await
+                .context("failed to read block from CAR file")?
+            {
                 if cid == find_cid {
                     stdout.write_all(&data).await?;
                 }
@@ -215,10 +455,14 @@ pub async fn run(
             let mut writer = CarWriter::new(CarHeader::V1(roots.into()), &mut car);
             for block in args.blocks {
                 eprintln!("adding block to car file: {block:?}");
-                let data = tokio::fs::read(block.path).await?;
+                let data = tokio::fs::read(&block.path)
+                    .await
+                    .with_context(|| format!("failed to read block file {}", block.path))?;
 
                 let hash = MultihashDigest::digest(
-                    &multihash_codetable::Code::try_from(block.cid.hash().code())?,
+                    &multihash_codetable::Code::try_from(block.cid.hash().code()).with_context(
+                        || format!("unsupported hash function of CID {}", block.cid),
+                    )?,
                     &data,
                 );
                 if hash != *block.cid.hash() {
@@ -231,22 +475,540 @@ pub async fn run(
 
             stdout.write_all(&car).await?;
         }
+        Operation::IpldTraverse(args) => {
+            let (roots, blocks) = read_car(stdin).await?;
+            let roots = match args.root {
+                Some(root) => vec![root],
+                None => roots,
+            };
+            let mut visited = HashMap::new();
+            let mut out = String::new();
+            for root in roots {
+                traverse(
+                    &root,
+                    &blocks,
+                    0,
+                    args.max_depth,
+                    &mut visited,
+                    style,
+                    &mut out,
+                )?;
+            }
+            stdout.write_all(out.as_bytes()).await?;
+        }
+        Operation::CarValidate => {
+            let (roots, blocks) = read_car(stdin).await?;
+            let report = validate_car(&roots, &blocks)?;
+            let out = match format {
+                OutputFormat::Text => {
+                    let mut out = String::new();
+                    for cid in &report.missing_roots {
+                        out.push_str(&format!("missing root: {cid}\n"));
+                    }
+                    for cid in &report.hash_mismatches {
+                        out.push_str(&format!("hash mismatch: {cid}\n"));
+                    }
+                    for link in &report.dangling_links {
+                        out.push_str(&format!(
+                            "dangling link: {} from {}\n",
+                            link.cid, link.parent
+                        ));
+                    }
+                    for cid in &report.unreferenced {
+                        out.push_str(&format!("unreferenced block: {cid}\n"));
+                    }
+                    out.push_str(&format!(
+                        "Blocks: {}\nComplete: {}\n",
+                        blocks.len(),
+                        report.complete
+                    ));
+                    out
+                }
+                OutputFormat::Json => format!("{}\n", serde_json::to_string(&report)?),
+            };
+            stdout.write_all(out.as_bytes()).await?;
+            if !report.complete {
+                bail!("CAR file is incomplete");
+            }
+        }
     };
     Ok(())
 }
 
-fn fmt_cid(cid: &Cid) -> Result<String> {
+/// Decode a block as IPLD data, returns None when the codec is not an IPLD codec.
+fn decode_block(cid: &Cid, data: &[u8]) -> Result<Option<Ipld>> {
+    Ok(match cid.codec() {
+        <DagCborCodec as Codec<Ipld>>::CODE => Some(
+            serde_ipld_dagcbor::from_slice(data)
+                .with_context(|| format!("failed to decode dag-cbor block {cid}"))?,
+        ),
+        <DagJsonCodec as Codec<Ipld>>::CODE => Some(
+            serde_ipld_dagjson::from_slice(data)
+                .with_context(|| format!("failed to decode dag-json block {cid}"))?,
+        ),
+        <DagJoseCodec as Codec<Ipld>>::CODE => Some(
+            DagJoseCodec::decode_from_slice(data)
+                .with_context(|| format!("failed to decode dag-jose block {cid}"))?,
+        ),
+        _ => None,
+    })
+}
+
+/// Result of validating that a CAR file is self-contained.
+#[derive(Serialize)]
+struct CarValidation {
+    complete: bool,
+    missing_roots: Vec<String>,
+    hash_mismatches: Vec<String>,
+    dangling_links: Vec<DanglingLink>,
+    unreferenced: Vec<String>,
+}
+
+/// A link to a block that is not in the CAR file.
+#[derive(Serialize)]
+struct DanglingLink {
+    cid: String,
+    parent: String,
+}
+
+/// Walk the DAG from the roots, finding links to missing blocks and blocks that are not
+/// reachable from any root. Blocks using an unsupported hash function are not verified.
+fn validate_car(roots: &[Cid], blocks: &HashMap<Cid, Vec<u8>>) -> Result<CarValidation> {
+    let missing_roots: Vec<String> = roots
+        .iter()
+        .filter(|root| !blocks.contains_key(root))
+        .map(Cid::to_string)
+        .collect();
+    let mut hash_mismatches: Vec<String> = blocks
+        .iter()
+        .filter(|(cid, data)| {
+            Code::try_from(cid.hash().code()).is_ok_and(|code| code.digest(data) != *cid.hash())
+        })
+        .map(|(cid, _)| cid.to_string())
+        .collect();
+    hash_mismatches.sort();
+    let mut dangling_links = Vec::new();
+    let mut visited = HashSet::new();
+    let mut pending: Vec<Cid> = roots
+        .iter()
+        .filter(|root| blocks.contains_key(root))
+        .copied()
+        .collect();
+    while let Some(cid) = pending.pop() {
+        if !visited.insert(cid) {
+            continue;
+        }
+        let Some(data) = decode_block(&cid, &blocks[&cid])? else {
+            continue;
+        };
+        let mut links = Vec::new();
+        collect_links(&data, &mut links);
+        for link in links {
+            if blocks.contains_key(&link) {
+                pending.push(link);
+            } else {
+                dangling_links.push(DanglingLink {
+                    cid: link.to_string(),
+                    parent: cid.to_string(),
+                });
+            }
+        }
+    }
+    let mut unreferenced: Vec<String> = blocks
+        .keys()
+        .filter(|cid| !visited.contains(cid))
+        .map(Cid::to_string)
+        .collect();
+    unreferenced.sort();
+    Ok(CarValidation {
+        complete: missing_roots.is_empty()
+            && hash_mismatches.is_empty()
+            && dangling_links.is_empty(),
+        missing_roots,
+        hash_mismatches,
+        dangling_links,
+        unreferenced,
+    })
+}
+
+/// Set the value at the path, creating maps for missing path segments.
+/// List indexes must exist, except the last segment may append to the list.
+fn set_path(node: &mut Ipld, path: &[&str], value: Ipld) -> Result<()> {
+    let Some((segment, rest)) = path.split_first() else {
+        *node = value;
+        return Ok(());
+    };
+    let child = match node {
+        Ipld::Map(map) => map
+            .entry(segment.to_string())
+            .or_insert_with(|| Ipld::Map(BTreeMap::new())),
+        Ipld::List(list) => {
+            let index: usize = segment
+                .parse()
+                .with_context(|| format!("invalid list index '{segment}'"))?;
+            if index == list.len() && rest.is_empty() {
+                list.push(value);
+                return Ok(());
+            }
+            let len = list.len();
+            list.get_mut(index)
+                .ok_or_else(|| anyhow!("list index {index} out of range for length {len}"))?
+        }
+        _ => bail!("cannot index into scalar value at '{segment}'"),
+    };
+    set_path(child, rest, value)
+}
+
+/// Name of the IPLD data model kind of the value.
+fn ipld_kind(data: &Ipld) -> &'static str {
+    match data {
+        Ipld::Null => "null",
+        Ipld::Bool(_) => "bool",
+        Ipld::Integer(_) => "integer",
+        Ipld::Float(_) => "float",
+        Ipld::String(_) => "string",
+        Ipld::Bytes(_) => "bytes",
+        Ipld::List(_) => "list",
+        Ipld::Map(_) => "map",
+        Ipld::Link(_) => "link",
+    }
+}
+
+/// Collect all links in IPLD data, in the order they appear.
+fn collect_links(data: &Ipld, links: &mut Vec<Cid>) {
+    match data {
+        Ipld::Link(cid) => links.push(*cid),
+        Ipld::List(list) => list.iter().for_each(|item| collect_links(item, links)),
+        Ipld::Map(map) => map.values().for_each(|value| collect_links(value, links)),
+        _ => {}
+    }
+}
+
+/// Name of well known IPLD codecs, otherwise the hex code.
+fn codec_name(codec: u64) -> String {
+    match codec {
+        RAW_CODEC => "raw".to_string(),
+        DAG_PB_CODEC => "dag-pb".to_string(),
+        <DagCborCodec as Codec<Ipld>>::CODE => "dag-cbor".to_string(),
+        <DagJsonCodec as Codec<Ipld>>::CODE => "dag-json".to_string(),
+        <DagJoseCodec as Codec<Ipld>>::CODE => "dag-jose".to_string(),
+        _ => format!("0x{codec:x}"),
+    }
+}
+
+/// Write a depth indented line for the node and recursively for each of its links.
+/// Nodes already expanded at the same or a shallower depth are marked as seen and not
+/// traversed again.
+fn traverse(
+    cid: &Cid,
+    blocks: &HashMap<Cid, Vec<u8>>,
+    depth: usize,
+    max_depth: usize,
+    visited: &mut HashMap<Cid, usize>,
+    style: Style,
+    out: &mut String,
+) -> Result<()> {
+    let indent = "  ".repeat(depth);
+    let node = format!("{indent}{} ({})", style.cid(cid), codec_name(cid.codec()));
+    let Some(data) = blocks.get(cid) else {
+        out.push_str(&format!("{node} missing\n"));
+        return Ok(());
+    };
+    if visited
+        .get(cid)
+        .is_some_and(|expanded_depth| *expanded_depth <= depth)
+    {
+        out.push_str(&format!("{node} seen\n"));
+        return Ok(());
+    }
+    let mut links = Vec::new();
+    if let Some(data) = decode_block(cid, data)? {
+        collect_links(&data, &mut links);
+    }
+    if depth >= max_depth && !links.is_empty() {
+        // Not marked as visited, a shallower path to the node may still expand it.
+        out.push_str(&format!("{node} max depth reached\n"));
+        return Ok(());
+    }
+    // Expanding at a shallower depth than before may reach links cut off by max depth.
+    visited.insert(*cid, depth);
+    out.push_str(&format!("{node}\n"));
+    for link in links {
+        traverse(&link, blocks, depth + 1, max_depth, visited, style, out)?;
+    }
+    Ok(())
+}
+
+/// Encode IPLD data as DAG-JSON, indented when pretty.
+fn dag_json(data: &Ipld, pretty: bool) -> Result<Vec<u8>> {
+    let out = serde_ipld_dagjson::to_vec(data)?;
+    if pretty {
+        // DAG-JSON is valid JSON, so links and bytes keep their {"/": ...} form.
+        let value: serde_json::Value = serde_json::from_slice(&out)?;
+        Ok(serde_json::to_vec_pretty(&value)?)
+    } else {
+        Ok(out)
+    }
+}
+
+/// Parse a CID argument, reading it from stdin when the argument is "-".
+async fn read_cid(cid: &str, stdin: &mut (impl AsyncRead + Unpin)) -> Result<Cid> {
+    if cid == "-" {
+        let mut data = Vec::new();
+        stdin
+            .read_to_end(&mut data)
+            .await
+            .context("failed to read CID from stdin")?;
+        let cid_str = std::str::from_utf8(&data)
+            .context("CID read from stdin is not valid UTF-8")?
+            .trim();
+        Cid::from_str(cid_str).with_context(|| format!("failed to parse CID '{cid_str}'"))
+    } else {
+        Cid::from_str(cid).with_context(|| format!("failed to parse CID '{cid}'"))
+    }
+}
+
+/// Decoded components of a CID.
+#[derive(Serialize)]
+struct CidInfo {
+    cid_v1: String,
+    cid_v0: Option<String>,
+    base32: Option<String>,
+    version: u64,
+    codec: u64,
+    hash_code: u64,
+    hash: String,
+}
+
+impl CidInfo {
+    fn new(cid: &Cid, base: multibase::Base) -> Result<Self> {
+        let v0 = Cid::new_v0(*cid.hash()).ok();
+        Ok(Self {
+            cid_v1: cid.into_v1()?.to_string_of_base(base)?,
+            cid_v0: v0.map(|v0| v0.to_string()),
+            base32: v0.map(|v0| multibase::encode(multibase::Base::Base32Upper, v0.to_bytes())),
+            version: cid.version().into(),
+            codec: cid.codec(),
+            hash_code: cid.hash().code(),
+            hash: hex::encode(cid.hash().digest()),
+        })
+    }
+}
+
+/// Length of a list, output by dag-cbor-keys for lists.
+#[derive(Serialize)]
+struct ListInfo {
+    length: usize,
+}
+
+/// Kind of a scalar, output by dag-cbor-keys for values that are not maps or lists.
+#[derive(Serialize)]
+struct ScalarInfo {
+    r#type: &'static str,
+}
+
+/// A block of a CAR file, with its data as DAG-JSON when the codec is known.
+#[derive(Serialize)]
+struct CarBlockInfo {
+    cid: CidInfo,
+    root: bool,
+    length: usize,
+    data: Option<serde_json::Value>,
+}
+
+fn fmt_cid(cid: &Cid, base: multibase::Base, style: Style) -> Result<String> {
     let (v0_str, v0_bytes) = Cid::new_v0(*cid.hash())
         .map(|v0| (v0.to_string(), v0.to_bytes()))
         .unwrap_or_default();
-    Ok(format!(
-        "CID V1: {}\nCID V0: {}\nBase32: {}\nVersion: {:?}\nCodec: 0x{:x}\nHash Code: 0x{:x}\nHash: 0x{}\n",
-        cid.into_v1()?,
-        &v0_str,
-        multibase::encode(multibase::Base::Base32Upper, &v0_bytes),
-        cid.version(),
-        cid.codec(),
-        cid.hash().code(),
-        hex::encode(cid.hash().digest())
-    ))
+    let fields = [
+        (
+            "CID V1:",
+            style.cid(cid.into_v1()?.to_string_of_base(base)?),
+        ),
+        ("CID V0:", style.cid(&v0_str)),
+        (
+            "Base32:",
+            style.cid(multibase::encode(multibase::Base::Base32Upper, &v0_bytes)),
+        ),
+        ("Version:", format!("{:?}", cid.version())),
+        ("Codec:", format!("0x{:x}", cid.codec())),
+        ("Hash Code:", format!("0x{:x}", cid.hash().code())),
+        ("Hash:", format!("0x{}", hex::encode(cid.hash().digest()))),
+    ];
+    Ok(fields
+        .into_iter()
+        .map(|(label, value)| format!("{} {value}\n", style.label(label)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::cli::{ColorChoice, InputEncoding};
+
+    async fn run_op(op: Operation, stdin: &[u8]) -> Vec<u8> {
+        let mut stdout = Vec::new();
+        run(
+            op,
+            OutputFormat::Text,
+            Style::new(ColorChoice::Never, false),
+            StdRng::seed_from_u64(0),
+            stdin,
+            &mut stdout,
+        )
+        .await
+        .unwrap();
+        stdout
+    }
+
+    async fn round_trip(hex: bool) {
+        let cid = Cid::new_v1(0x71, Code::Sha2_256.digest(b"hello world"));
+        let bytes = run_op(
+            Operation::CidAsBytes(CidAsBytesArgs {
+                cid: cid.to_string(),
+                hex,
+            }),
+            &[],
+        )
+        .await;
+        let encoding = if hex {
+            InputEncoding::Hex
+        } else {
+            InputEncoding::Raw
+        };
+        let out = run_op(
+            Operation::CidFromBytes(CidFromBytesArgs { encoding }),
+            &bytes,
+        )
+        .await;
+        assert_eq!(String::from_utf8(out).unwrap(), format!("{cid}\n"));
+    }
+
+    #[tokio::test]
+    async fn cid_bytes_round_trip() {
+        round_trip(false).await;
+    }
+
+    #[tokio::test]
+    async fn cid_bytes_round_trip_hex() {
+        round_trip(true).await;
+    }
+
+    fn dag_json(json: &str) -> Ipld {
+        serde_ipld_dagjson::from_slice(json.as_bytes()).unwrap()
+    }
+
+    fn set(json: &str, path: &str, value: &str) -> Result<Ipld> {
+        let mut data = dag_json(json);
+        let path: Vec<&str> = path.split('/').collect();
+        set_path(&mut data, &path, dag_json(value))?;
+        Ok(data)
+    }
+
+    #[tokio::test]
+    async fn dag_cbor_set() {
+        let input = serde_ipld_dagcbor::to_vec(&dag_json(r#"{"a":{"b":[1,2]}}"#)).unwrap();
+        let out = run_op(
+            Operation::DagCborSet(DagCborSetArgs {
+                path: "a/b/1".to_string(),
+                value_json: r#""x""#.to_string(),
+            }),
+            &input,
+        )
+        .await;
+        assert_eq!(
+            serde_ipld_dagcbor::from_slice::<Ipld>(&out).unwrap(),
+            dag_json(r#"{"a":{"b":[1,"x"]}}"#)
+        );
+    }
+
+    #[test]
+    fn set_path_creates_maps() {
+        assert_eq!(
+            set(r#"{"a":1}"#, "b/c/d", "true").unwrap(),
+            dag_json(r#"{"a":1,"b":{"c":{"d":true}}}"#)
+        );
+    }
+
+    #[test]
+    fn set_path_list_index() {
+        assert_eq!(
+            set(r#"{"a":[1,{"b":2}]}"#, "a/1/b", "3").unwrap(),
+            dag_json(r#"{"a":[1,{"b":3}]}"#)
+        );
+        // The index one past the end appends to the list.
+        assert_eq!(set(r#"[1,2]"#, "2", "3").unwrap(), dag_json(r#"[1,2,3]"#));
+    }
+
+    #[test]
+    fn set_path_invalid_index() {
+        let err = set(r#"[1,2]"#, "3", "3").unwrap_err();
+        assert_eq!(err.to_string(), "list index 3 out of range for length 2");
+        // Only the last segment may append.
+        let err = set(r#"[1,2]"#, "2/a", "3").unwrap_err();
+        assert_eq!(err.to_string(), "list index 2 out of range for length 2");
+        let err = set(r#"[1,2]"#, "first", "3").unwrap_err();
+        assert_eq!(err.to_string(), "invalid list index 'first'");
+        let err = set(r#"{"a":1}"#, "a/b", "3").unwrap_err();
+        assert_eq!(err.to_string(), "cannot index into scalar value at 'b'");
+    }
+
+    #[tokio::test]
+    async fn validate_car_invalid() {
+        let leaf = b"leaf".to_vec();
+        let leaf_cid = Cid::new_v1(RAW_CODEC, Code::Sha2_256.digest(&leaf));
+        let node = serde_ipld_dagcbor::to_vec(&Ipld::List(vec![Ipld::Link(leaf_cid)])).unwrap();
+        let node_cid = Cid::new_v1(
+            <DagCborCodec as Codec<Ipld>>::CODE,
+            Code::Sha2_256.digest(&node),
+        );
+        let missing = Cid::new_v1(RAW_CODEC, Code::Sha2_256.digest(b"missing"));
+
+        let mut car = Vec::new();
+        let mut writer = CarWriter::new(CarHeader::V1(vec![node_cid, missing].into()), &mut car);
+        writer.write(node_cid, node).await.unwrap();
+        // The data of the leaf does not match the hash of its CID.
+        writer.write(leaf_cid, b"tampered".to_vec()).await.unwrap();
+        writer.finish().await.unwrap();
+
+        let (roots, blocks) = read_car(car.as_slice()).await.unwrap();
+        let report = validate_car(&roots, &blocks).unwrap();
+        assert!(!report.complete);
+        assert_eq!(report.missing_roots, vec![missing.to_string()]);
+        assert_eq!(report.hash_mismatches, vec![leaf_cid.to_string()]);
+        assert!(report.dangling_links.is_empty());
+        assert!(report.unreferenced.is_empty());
+    }
+
+    #[tokio::test]
+    async fn validate_car_complete() {
+        let leaf = b"leaf".to_vec();
+        let leaf_cid = Cid::new_v1(RAW_CODEC, Code::Sha2_256.digest(&leaf));
+        let mut car = Vec::new();
+        let mut writer = CarWriter::new(CarHeader::V1(vec![leaf_cid].into()), &mut car);
+        writer.write(leaf_cid, leaf).await.unwrap();
+        writer.finish().await.unwrap();
+
+        let (roots, blocks) = read_car(car.as_slice()).await.unwrap();
+        let report = validate_car(&roots, &blocks).unwrap();
+        assert!(report.complete);
+        assert!(report.hash_mismatches.is_empty());
+    }
+
+    #[tokio::test]
+    async fn cid_from_data_multiple_chunks() {
+        // Spans several chunks and ends with a partial one.
+        let data: Vec<u8> = (0..3 * crate::multihash::DIGEST_CHUNK_SIZE + 17)
+            .map(|i| i as u8)
+            .collect();
+        let out = run_op(
+            Operation::CidFromData(CidFromDataArgs { codec: RAW_CODEC }),
+            &data,
+        )
+        .await;
+        let cid = Cid::new_v1(RAW_CODEC, Code::Sha2_256.digest(&data));
+        assert_eq!(String::from_utf8(out).unwrap(), format!("{cid}\n"));
+    }
 }
@@ -10,28 +10,168 @@ mod multihash;
 mod p2p;
 
 pub mod cli;
+// Only the IPLD inspection commands style their output.
+#[cfg_attr(not(feature = "ipld"), allow(dead_code))]
+mod style;
 
 pub use cli::Cli;
 
-use clap::CommandFactory;
-use tokio::io::{AsyncRead, AsyncWrite};
+use std::{io::IsTerminal, path::Path, pin::Pin};
 
-pub async fn run(
+use anyhow::{bail, Context};
+use clap::{CommandFactory, ValueEnum};
+use futures::pin_mut;
+use serde::Serialize;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+
+pub async fn run<'a>(
     args: Cli,
+    stdin: impl AsyncRead + Send + 'a,
+    stdout: impl AsyncWrite + Send + 'a,
+) -> anyhow::Result<()> {
+    // --seed is global so clap cannot reject it together with a subcommand's --key-file,
+    // and a key derived from the seed must not be persisted.
+    if args.seed.is_some()
+        && args
+            .command
+            .swarm_args()
+            .is_some_and(|swarm| swarm.key_file.is_some())
+    {
+        bail!("--seed cannot be used with --key-file");
+    }
+    let stdin: Pin<Box<dyn AsyncRead + Send + 'a>> = match args.input.as_deref() {
+        Some(path) if path != Path::new("-") => Box::pin(
+            tokio::fs::File::open(path)
+                .await
+                .with_context(|| format!("failed to open input file {}", path.display()))?,
+        ),
+        _ => Box::pin(stdin),
+    };
+    let mut stdout: Pin<Box<dyn AsyncWrite + Send + 'a>> = match args.output.as_deref() {
+        Some(path) if path != Path::new("-") => Box::pin(
+            tokio::fs::File::create(path)
+                .await
+                .with_context(|| format!("failed to create output file {}", path.display()))?,
+        ),
+        _ => Box::pin(stdout),
+    };
+    let to_stdout = !matches!(args.output.as_deref(), Some(path) if path != Path::new("-"));
+    let style = style::Style::new(args.color, to_stdout && std::io::stdout().is_terminal());
+    let format = args.output_format();
+    let rng = args.rng();
+    let result = run_command(args.command, format, style, rng, stdin, &mut stdout).await;
+    stdout.flush().await?;
+    result
+}
+
+async fn run_command(
+    command: cli::Command,
+    format: cli::OutputFormat,
+    #[allow(unused)] style: style::Style,
+    #[allow(unused)] rng: rand::rngs::StdRng,
     stdin: impl AsyncRead + Send,
     stdout: impl AsyncWrite + Send,
 ) -> anyhow::Result<()> {
     // Generate shell completetions
-    if let cli::Command::Completion(args) = &args.command {
-        args.shell
-            // TODO: use async stdout
-            .generate(&mut Cli::command(), &mut std::io::stdout());
+    if let cli::Command::Completion(args) = &command {
+        let shells = if args.all_shells {
+            clap_complete_command::Shell::value_variants().to_vec()
+        } else {
+            args.shell.into_iter().collect()
+        };
+        match &args.output_dir {
+            Some(dir) => {
+                tokio::fs::create_dir_all(dir).await.with_context(|| {
+                    format!("failed to create completion directory {}", dir.display())
+                })?;
+                for shell in shells {
+                    let mut script = Vec::new();
+                    shell.generate(&mut Cli::command(), &mut script);
+                    let name = shell
+                        .to_possible_value()
+                        .map(|value| value.get_name().to_string())
+                        .unwrap_or_else(|| format!("{shell:?}").to_lowercase());
+                    let path = dir.join(format!("cpk.{name}"));
+                    tokio::fs::write(&path, script).await.with_context(|| {
+                        format!("failed to write completion script {}", path.display())
+                    })?;
+                    log::info!("Wrote {}", path.display());
+                }
+            }
+            None => {
+                let mut script = Vec::new();
+                for shell in shells {
+                    shell.generate(&mut Cli::command(), &mut script);
+                }
+                pin_mut!(stdout);
+                stdout.write_all(&script).await?;
+            }
+        }
+        return Ok(());
+    };
+
+    // Print build information
+    if let cli::Command::BuildInfo = &command {
+        let info = BuildInfo {
+            version: env!("CARGO_PKG_VERSION"),
+            git_commit: env!("GIT_COMMIT"),
+            features: enabled_features(),
+            target: env!("TARGET"),
+        };
+        let out = if format == cli::OutputFormat::Json {
+            serde_json::to_string(&info)?
+        } else {
+            format!(
+                "version: {}\ncommit: {}\nfeatures: {}\ntarget: {}",
+                info.version,
+                info.git_commit,
+                info.features.join(", "),
+                info.target
+            )
+        };
+        pin_mut!(stdout);
+        stdout.write_all(format!("{out}\n").as_bytes()).await?;
+        return Ok(());
+    };
+
+    // Generate man pages
+    if let cli::Command::Man(args) = &command {
+        let mut cmd = Cli::command();
+        cmd.build();
+        match &args.output_dir {
+            Some(dir) => {
+                tokio::fs::create_dir_all(dir).await.with_context(|| {
+                    format!("failed to create man page directory {}", dir.display())
+                })?;
+                let name = cmd.get_name().to_string();
+                let mut pages = vec![(name.clone(), cmd.clone())];
+                for sub in cmd.get_subcommands() {
+                    let sub_name = format!("{name}-{}", sub.get_name());
+                    pages.push((sub_name.clone(), sub.clone().name(sub_name)));
+                }
+                for (name, page) in pages {
+                    let mut roff = Vec::new();
+                    clap_mangen::Man::new(page).render(&mut roff)?;
+                    let path = dir.join(format!("{name}.1"));
+                    tokio::fs::write(&path, roff)
+                        .await
+                        .with_context(|| format!("failed to write man page {}", path.display()))?;
+                    log::info!("Wrote {}", path.display());
+                }
+            }
+            None => {
+                let mut roff = Vec::new();
+                clap_mangen::Man::new(cmd).render(&mut roff)?;
+                pin_mut!(stdout);
+                stdout.write_all(&roff).await?;
+            }
+        }
         return Ok(());
     };
 
     // Try each category of command in turn, until we find a match.
     #[allow(unused)]
-    let cmd = args.command;
+    let cmd = command;
 
     #[cfg(feature = "multibase")]
     #[allow(unused)]
@@ -46,7 +186,7 @@ pub async fn run(
     #[allow(unused)]
     let cmd = match ipld::Operation::try_from(cmd) {
         Ok(op) => {
-            return ipld::run(op, stdin, stdout).await;
+            return ipld::run(op, format, style, rng, stdin, stdout).await;
         }
         Err(cmd) => cmd,
     };
@@ -55,7 +195,7 @@ pub async fn run(
     #[allow(unused)]
     let cmd = match ceramic::Operation::try_from(cmd) {
         Ok(op) => {
-            return ceramic::run(op, stdin, stdout).await;
+            return ceramic::run(op, format, rng, stdin, stdout).await;
         }
         Err(cmd) => cmd,
     };
@@ -64,7 +204,7 @@ pub async fn run(
     #[allow(unused)]
     let cmd = match multihash::Operation::try_from(cmd) {
         Ok(op) => {
-            return multihash::run(op, stdin, stdout).await;
+            return multihash::run(op, format, stdin, stdout).await;
         }
         Err(cmd) => cmd,
     };
@@ -73,20 +213,108 @@ pub async fn run(
     #[allow(unused)]
     let cmd = match p2p::Operation::try_from(cmd) {
         Ok(op) => {
-            return p2p::run(op, stdin, stdout).await;
+            return p2p::run(op, format, rng, stdin, stdout).await;
         }
         Err(cmd) => cmd,
     };
     Err(anyhow::anyhow!("failed to match command"))
 }
 
+/// Version and configuration of this build.
+#[derive(Serialize)]
+struct BuildInfo {
+    version: &'static str,
+    git_commit: &'static str,
+    features: Vec<&'static str>,
+    target: &'static str,
+}
+
+/// Cargo features enabled at compile time.
+fn enabled_features() -> Vec<&'static str> {
+    [
+        ("ceramic", cfg!(feature = "ceramic")),
+        ("ipld", cfg!(feature = "ipld")),
+        ("multibase", cfg!(feature = "multibase")),
+        ("multihash", cfg!(feature = "multihash")),
+        ("p2p", cfg!(feature = "p2p")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
+
+/// Read all blocks of a CAR file, returning its roots and blocks by CID.
+#[cfg(any(feature = "ipld", feature = "ceramic"))]
+async fn read_car(
+    stdin: impl AsyncRead + Send + Unpin,
+) -> anyhow::Result<(Vec<cid::Cid>, std::collections::HashMap<cid::Cid, Vec<u8>>)> {
+    let mut reader = iroh_car::CarReader::new(stdin)
+        .await
+        .context("failed to read CAR file header")?;
+    let roots = reader.header().roots().to_vec();
+    let mut blocks = std::collections::HashMap::new();
+    while let Some((cid, data)) = reader
+        .next_block()
+        .await
+        .context("failed to read block from CAR file")?
+    {
+        blocks.insert(cid, data);
+    }
+    Ok((roots, blocks))
+}
+
+/// Multicodec code for raw binary data
+#[cfg(any(feature = "ipld", feature = "ceramic"))]
+const RAW_CODEC: u64 = 0x55;
+
 #[cfg(any(feature = "ipld", feature = "ceramic"))]
-fn random_cid() -> cid::Cid {
+fn random_cid(rng: &mut impl rand::Rng) -> cid::Cid {
     use multihash_codetable::Code;
     use multihash_derive::MultihashDigest;
 
     let mut data = [0u8; 8];
-    rand::Rng::fill(&mut rand::thread_rng(), &mut data);
+    rng.fill(&mut data);
     let hash = Code::Sha2_256.digest(&data);
     cid::Cid::new_v1(0x00, hash)
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn output_file_matches_stdout() {
+        let mut stdout = Vec::new();
+        run(
+            Cli::try_parse_from(["cpk", "build-info"]).unwrap(),
+            tokio::io::empty(),
+            &mut stdout,
+        )
+        .await
+        .unwrap();
+
+        let path = std::env::temp_dir().join(format!("cpk-output-{}", std::process::id()));
+        let mut unused = Vec::new();
+        run(
+            Cli::try_parse_from([
+                "cpk".as_ref(),
+                "build-info".as_ref(),
+                "--output".as_ref(),
+                path.as_os_str(),
+            ])
+            .unwrap(),
+            tokio::io::empty(),
+            &mut unused,
+        )
+        .await
+        .unwrap();
+        let file = tokio::fs::read(&path).await.unwrap();
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        assert!(!stdout.is_empty());
+        assert_eq!(file, stdout);
+        assert!(unused.is_empty());
+    }
+}
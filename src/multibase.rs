@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use futures::pin_mut;
 use multibase::Base;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
@@ -65,9 +65,11 @@ pub async fn run(op: Operation, stdin: impl AsyncRead, stdout: impl AsyncWrite)
             }
         }
         Operation::Decode => {
-            stdout
-                .write_all(&multibase::decode(input_utf8(stdin).await?.trim_end())?.1)
-                .await?
+            let input = input_utf8(stdin).await?;
+            let (_base, data) = multibase::decode(input.trim_end()).with_context(|| {
+                format!("failed to decode multibase input '{}'", input.trim_end())
+            })?;
+            stdout.write_all(&data).await?
         }
         Operation::Base2 => encode(stdin, stdout, Base::Base2).await?,
         Operation::Base8 => encode(stdin, stdout, Base::Base8).await?,
@@ -92,14 +94,20 @@ pub async fn run(op: Operation, stdin: impl AsyncRead, stdout: impl AsyncWrite)
 async fn input_bytes(stdin: impl AsyncRead) -> Result<Vec<u8>> {
     pin_mut!(stdin);
     let mut data = Vec::new();
-    stdin.read_to_end(&mut data).await?;
+    stdin
+        .read_to_end(&mut data)
+        .await
+        .context("failed to read input from stdin")?;
     Ok(data)
 }
 async fn input_utf8(stdin: impl AsyncRead) -> Result<String> {
     pin_mut!(stdin);
     let mut data = Vec::new();
-    stdin.read_to_end(&mut data).await?;
-    Ok(String::from_utf8(data)?)
+    stdin
+        .read_to_end(&mut data)
+        .await
+        .context("failed to read input from stdin")?;
+    String::from_utf8(data).context("input read from stdin is not valid UTF-8")
 }
 
 async fn encode(stdin: impl AsyncRead, stdout: impl AsyncWrite, base: Base) -> Result<()> {
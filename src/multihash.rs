@@ -1,12 +1,29 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Context, Result};
+use cid::Cid;
 use futures::pin_mut;
 use multihash::Multihash;
+use multihash_codetable::{
+    Blake3_256, Code, Keccak224, Keccak256, Keccak384, Keccak512, MultihashDigest, Sha2_256,
+    Sha2_512, Sha3_224, Sha3_256, Sha3_384, Sha3_512,
+};
+use multihash_derive::Hasher;
+use serde::Serialize;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
-use crate::cli::Command;
+use crate::cli::{
+    Command, HashCode, InputEncoding, MultihashCreateArgs, MultihashFromDigestArgs,
+    MultihashInspectArgs, MultihashToCidArgs, MultihashVerifyArgs, OutputFormat,
+};
+
+/// Multihash code of the identity hash function
+const IDENTITY: u64 = 0x00;
 
 pub enum Operation {
-    MultihashInspect,
+    MultihashInspect(MultihashInspectArgs),
+    MultihashCreate(MultihashCreateArgs),
+    MultihashVerify(MultihashVerifyArgs),
+    MultihashFromDigest(MultihashFromDigestArgs),
+    MultihashToCid(MultihashToCidArgs),
 }
 
 impl TryFrom<Command> for Operation {
@@ -14,31 +31,352 @@ impl TryFrom<Command> for Operation {
 
     fn try_from(value: Command) -> std::result::Result<Self, Self::Error> {
         match value {
-            Command::MultihashInspect => Ok(Operation::MultihashInspect),
+            Command::MultihashInspect(args) => Ok(Operation::MultihashInspect(args)),
+            Command::MultihashCreate(args) => Ok(Operation::MultihashCreate(args)),
+            Command::MultihashVerify(args) => Ok(Operation::MultihashVerify(args)),
+            Command::MultihashFromDigest(args) => Ok(Operation::MultihashFromDigest(args)),
+            Command::MultihashToCid(args) => Ok(Operation::MultihashToCid(args)),
             _ => Err(value),
         }
     }
 }
 
-pub async fn run(op: Operation, stdin: impl AsyncRead, stdout: impl AsyncWrite) -> Result<()> {
+pub async fn run(
+    op: Operation,
+    format: OutputFormat,
+    stdin: impl AsyncRead,
+    stdout: impl AsyncWrite,
+) -> Result<()> {
     pin_mut!(stdin, stdout);
     match op {
-        Operation::MultihashInspect => {
+        Operation::MultihashInspect(args) => {
             let mut bytes = Vec::with_capacity(1024);
-            stdin.read_to_end(&mut bytes).await?;
-            let hash: Multihash<32> = Multihash::from_bytes(&bytes)?;
-            stdout
-                .write_all(
-                    format!(
-                        "Code: {}\nSize: {}\nDigest(hex): {}\n",
-                        hash.code(),
-                        hash.size(),
-                        hex::encode(hash.digest())
-                    )
-                    .as_bytes(),
-                )
-                .await?;
+            stdin
+                .read_to_end(&mut bytes)
+                .await
+                .context("failed to read multihash from stdin")?;
+            if args.lines {
+                if matches!(args.encoding, InputEncoding::Raw) {
+                    bail!("--lines requires a text encoding, i.e. --encoding hex");
+                }
+                if format == OutputFormat::Text {
+                    stdout.write_all(b"Code\tName\tSize\tDigest(hex)\n").await?;
+                }
+                for line in String::from_utf8(bytes)
+                    .context("multihash lines read from stdin are not valid UTF-8")?
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                {
+                    let hash = args
+                        .encoding
+                        .decode(line.as_bytes().to_vec())
+                        .and_then(|bytes| Ok(Multihash::<64>::from_bytes(&bytes)?));
+                    let row = match (hash, format) {
+                        (Ok(hash), OutputFormat::Text) => {
+                            let (name, _) = hash_info(hash.code()).unwrap_or(("(unknown)", None));
+                            format!(
+                                "{}\t{name}\t{}\t{}\n",
+                                hash.code(),
+                                hash.size(),
+                                hex::encode(hash.digest())
+                            )
+                        }
+                        (Ok(hash), OutputFormat::Json) => {
+                            format!("{}\n", serde_json::to_string(&MultihashInfo::new(&hash))?)
+                        }
+                        (Err(err), OutputFormat::Text) => format!("{line}\tinvalid: {err}\n"),
+                        (Err(err), OutputFormat::Json) => format!(
+                            "{}\n",
+                            serde_json::to_string(&InvalidMultihash {
+                                input: line,
+                                error: err.to_string(),
+                            })?
+                        ),
+                    };
+                    stdout.write_all(row.as_bytes()).await?;
+                }
+            } else {
+                let bytes = args
+                    .encoding
+                    .decode(bytes)
+                    .context("failed to decode multihash input")?;
+                let hash: Multihash<64> =
+                    Multihash::from_bytes(&bytes).context("failed to parse multihash")?;
+                let out = match format {
+                    OutputFormat::Text => fmt_multihash(&hash),
+                    OutputFormat::Json => {
+                        format!("{}\n", serde_json::to_string(&MultihashInfo::new(&hash))?)
+                    }
+                };
+                stdout.write_all(out.as_bytes()).await?;
+            }
+        }
+        Operation::MultihashCreate(args) => {
+            let hash = match convert_code(args.code) {
+                Some(code) => digest_reader(code, &mut stdin).await?,
+                None => {
+                    // Identity digests hold the data itself, so it must be read into memory.
+                    let mut data = Vec::new();
+                    stdin
+                        .read_to_end(&mut data)
+                        .await
+                        .context("failed to read data from stdin")?;
+                    Multihash::<64>::wrap(IDENTITY, &data)?
+                }
+            };
+            if args.hex {
+                stdout
+                    .write_all(format!("{}\n", hex::encode(hash.to_bytes())).as_bytes())
+                    .await?;
+            } else {
+                stdout.write_all(&hash.to_bytes()).await?;
+            }
+        }
+        Operation::MultihashVerify(args) => {
+            let hash = parse_multihash(&args.multihash)
+                .with_context(|| format!("failed to parse multihash '{}'", args.multihash))?;
+            let mut data = Vec::new();
+            stdin
+                .read_to_end(&mut data)
+                .await
+                .context("failed to read data from stdin")?;
+            let matches = if hash.code() == IDENTITY {
+                hash.digest() == data
+            } else {
+                Code::try_from(hash.code())
+                    .with_context(|| format!("unsupported hash function 0x{:x}", hash.code()))?
+                    .digest(&data)
+                    == hash
+            };
+            if matches {
+                stdout.write_all(b"match\n").await?;
+            } else {
+                stdout.write_all(b"mismatch\n").await?;
+                bail!("multihash does not match data");
+            }
+        }
+        Operation::MultihashFromDigest(args) => {
+            let code = parse_code(&args.code)?;
+            let digest = hex::decode(args.digest_hex.trim())
+                .with_context(|| format!("failed to decode hex digest '{}'", args.digest_hex))?;
+            if let Some((name, Some(expected))) = hash_info(code) {
+                if digest.len() != expected as usize {
+                    bail!(
+                        "{name} digests are {expected} bytes, found {} bytes",
+                        digest.len()
+                    );
+                }
+            }
+            let hash = Multihash::<64>::wrap(code, &digest)?;
+            if args.hex {
+                stdout
+                    .write_all(format!("{}\n", hex::encode(hash.to_bytes())).as_bytes())
+                    .await?;
+            } else {
+                stdout.write_all(&hash.to_bytes()).await?;
+            }
+        }
+        Operation::MultihashToCid(args) => {
+            let mut data = Vec::new();
+            stdin
+                .read_to_end(&mut data)
+                .await
+                .context("failed to read multihash from stdin")?;
+            let value = String::from_utf8(data).context("multihash is not valid UTF-8")?;
+            let hash = parse_multihash(value.trim())
+                .with_context(|| format!("failed to parse multihash '{}'", value.trim()))?;
+            let cid = Cid::new_v1(args.codec, hash);
+            stdout.write_all(format!("{cid}\n").as_bytes()).await?;
         }
     };
     Ok(())
 }
+
+/// Size of the chunks read while hashing, so memory use is bounded for large inputs.
+pub(crate) const DIGEST_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Hash all data from the reader incrementally in fixed size chunks.
+pub(crate) async fn digest_reader(
+    code: Code,
+    mut reader: impl AsyncRead + Unpin,
+) -> Result<Multihash<64>> {
+    let mut hasher: Box<dyn Hasher + Send> = match code {
+        Code::Sha2_256 => Box::<Sha2_256>::default(),
+        Code::Sha2_512 => Box::<Sha2_512>::default(),
+        Code::Sha3_224 => Box::<Sha3_224>::default(),
+        Code::Sha3_256 => Box::<Sha3_256>::default(),
+        Code::Sha3_384 => Box::<Sha3_384>::default(),
+        Code::Sha3_512 => Box::<Sha3_512>::default(),
+        Code::Keccak224 => Box::<Keccak224>::default(),
+        Code::Keccak256 => Box::<Keccak256>::default(),
+        Code::Keccak384 => Box::<Keccak384>::default(),
+        Code::Keccak512 => Box::<Keccak512>::default(),
+        Code::Blake3_256 => Box::<Blake3_256>::default(),
+        // Only reachable when other multihash-codetable features are enabled by a dependency.
+        #[allow(unreachable_patterns)]
+        _ => bail!("streaming is not supported for hash function {code:?}"),
+    };
+    let mut buf = vec![0u8; DIGEST_CHUNK_SIZE];
+    loop {
+        let n = reader
+            .read(&mut buf)
+            .await
+            .context("failed to read data from stdin")?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(Multihash::wrap(code.into(), hasher.finalize())?)
+}
+
+/// Decoded components of a multihash.
+#[derive(Serialize)]
+struct MultihashInfo {
+    code: u64,
+    name: Option<&'static str>,
+    size: u8,
+    expected_size: Option<u8>,
+    digest: String,
+}
+
+impl MultihashInfo {
+    fn new(hash: &Multihash<64>) -> Self {
+        let (name, expected_size) = hash_info(hash.code()).unzip();
+        Self {
+            code: hash.code(),
+            name,
+            size: hash.size(),
+            expected_size: expected_size.flatten(),
+            digest: hex::encode(hash.digest()),
+        }
+    }
+}
+
+/// A line of multihash-inspect --lines that failed to decode.
+#[derive(Serialize)]
+struct InvalidMultihash<'a> {
+    input: &'a str,
+    error: String,
+}
+
+fn fmt_multihash(hash: &Multihash<64>) -> String {
+    let (name, expected_size) = hash_info(hash.code()).unwrap_or(("(unknown)", None));
+    let size = match expected_size {
+        Some(expected) if expected != hash.size() => format!(
+            "{} (expected {expected} for {name}, the digest may be truncated or corrupt)",
+            hash.size()
+        ),
+        _ => hash.size().to_string(),
+    };
+    format!(
+        "Code: {}\nName: {name}\nSize: {size}\nDigest(hex): {}\n",
+        hash.code(),
+        hex::encode(hash.digest())
+    )
+}
+
+/// Multicodec code, canonical name and expected digest size of well known hash functions.
+/// The size is None for hash functions with a variable digest size.
+const HASH_FUNCTIONS: [(u64, &str, Option<u8>); 22] = [
+    (0x00, "identity", None),
+    (0x11, "sha1", Some(20)),
+    (0x12, "sha2-256", Some(32)),
+    (0x13, "sha2-512", Some(64)),
+    (0x14, "sha3-512", Some(64)),
+    (0x15, "sha3-384", Some(48)),
+    (0x16, "sha3-256", Some(32)),
+    (0x17, "sha3-224", Some(28)),
+    (0x1a, "keccak-224", Some(28)),
+    (0x1b, "keccak-256", Some(32)),
+    (0x1c, "keccak-384", Some(48)),
+    (0x1d, "keccak-512", Some(64)),
+    (0x1e, "blake3", None),
+    (0x20, "sha2-384", Some(48)),
+    (0x1013, "sha2-224", Some(28)),
+    (0x1053, "ripemd-160", Some(20)),
+    (0x1054, "ripemd-256", Some(32)),
+    (0x1055, "ripemd-320", Some(40)),
+    (0xb220, "blake2b-256", Some(32)),
+    (0xb240, "blake2b-512", Some(64)),
+    (0xb250, "blake2s-128", Some(16)),
+    (0xb260, "blake2s-256", Some(32)),
+];
+
+/// Canonical multicodec name and expected digest size of well known hash functions.
+fn hash_info(code: u64) -> Option<(&'static str, Option<u8>)> {
+    HASH_FUNCTIONS
+        .iter()
+        .find(|(known, _, _)| *known == code)
+        .map(|(_, name, size)| (*name, *size))
+}
+
+/// Parse a hash function name or its multicodec code as decimal or 0x prefixed hex.
+fn parse_code(value: &str) -> Result<u64> {
+    if let Some(digits) = value.strip_prefix("0x") {
+        return u64::from_str_radix(digits, 16)
+            .with_context(|| format!("invalid hex hash function code '{value}'"));
+    }
+    if let Ok(code) = value.parse() {
+        return Ok(code);
+    }
+    HASH_FUNCTIONS
+        .iter()
+        .find(|(_, name, _)| *name == value)
+        .map(|(code, _, _)| *code)
+        .ok_or_else(|| anyhow!("unknown hash function '{value}'"))
+}
+
+/// Parse a multihash encoded as hex or a multibase string.
+/// Strings without a multibase prefix, i.e. Qm..., are assumed to be base58btc.
+fn parse_multihash(value: &str) -> Result<Multihash<64>> {
+    let bytes = hex::decode(value)
+        .or_else(|_| multibase::decode(value).map(|(_base, bytes)| bytes))
+        .or_else(|_| multibase::Base::Base58Btc.decode(value))?;
+    Ok(Multihash::from_bytes(&bytes)?)
+}
+
+/// Convert to the code table hash function, returns None for the identity hash.
+pub(crate) fn convert_code(value: HashCode) -> Option<Code> {
+    match value {
+        HashCode::Identity => None,
+        HashCode::Sha2_256 => Some(Code::Sha2_256),
+        HashCode::Sha2_512 => Some(Code::Sha2_512),
+        HashCode::Sha3_224 => Some(Code::Sha3_224),
+        HashCode::Sha3_256 => Some(Code::Sha3_256),
+        HashCode::Sha3_384 => Some(Code::Sha3_384),
+        HashCode::Sha3_512 => Some(Code::Sha3_512),
+        HashCode::Keccak256 => Some(Code::Keccak256),
+        HashCode::Blake3_256 => Some(Code::Blake3_256),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn inspect_sha2_512() {
+        let hash = Code::Sha2_512.digest(b"hello world");
+        let mut stdout = Vec::new();
+        run(
+            Operation::MultihashInspect(MultihashInspectArgs {
+                encoding: InputEncoding::Raw,
+                lines: false,
+            }),
+            OutputFormat::Json,
+            hash.to_bytes().as_slice(),
+            &mut stdout,
+        )
+        .await
+        .unwrap();
+
+        let info: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+        assert_eq!(info["name"], "sha2-512");
+        assert_eq!(info["code"], 0x13);
+        assert_eq!(info["size"], 64);
+        assert_eq!(info["expected_size"], 64);
+        assert_eq!(info["digest"], hex::encode(hash.digest()));
+    }
+}
@@ -1,21 +1,63 @@
-use std::time::Duration;
+mod bitswap;
 
-use anyhow::Result;
+use std::{
+    collections::{BTreeSet, HashMap},
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use hickory_resolver::TokioAsyncResolver;
 use libp2p::{
+    autonat,
     futures::{pin_mut, StreamExt},
-    identify,
+    gossipsub, identify,
     identity::{self, Keypair},
+    kad, mdns,
+    multiaddr::Protocol,
     noise, ping,
-    swarm::{NetworkBehaviour, SwarmEvent},
-    tcp, tls, yamux, Multiaddr, Swarm, SwarmBuilder,
+    swarm::{dial_opts::DialOpts, dummy, NetworkBehaviour, SwarmEvent},
+    tcp, tls, yamux, Multiaddr, PeerId, StreamProtocol, Swarm, SwarmBuilder,
+};
+use rand::{rngs::StdRng, Rng};
+use serde::Serialize;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    signal,
+    time::{timeout_at, Instant},
 };
-use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
 
-use crate::cli::{Command, IdentifyArgs, PingArgs};
+use crate::cli::{
+    AutonatArgs, Command, ConnectTimeArgs, DhtProvidersArgs, DiscoverArgs, FindPeerArgs,
+    GetBlockArgs, GossipsubListenArgs, IdentifyArgs, ListenArgs, OutputFormat, PingArgs,
+    PingFormat, ProbeArgs, SwarmArgs, Transport,
+};
 
 pub enum Operation {
     Ping(PingArgs),
     Identify(IdentifyArgs),
+    FindPeer(FindPeerArgs),
+    DhtProviders(DhtProvidersArgs),
+    GossipsubListen(GossipsubListenArgs),
+    Listen(ListenArgs),
+    Discover(DiscoverArgs),
+    Probe(ProbeArgs),
+    ConnectTime(ConnectTimeArgs),
+    GetBlock(GetBlockArgs),
+    Autonat(AutonatArgs),
+}
+
+/// Behaviour of a peer that responds to ping and identify requests.
+#[derive(NetworkBehaviour)]
+struct PeerBehaviour {
+    ping: ping::Behaviour,
+    identify: identify::Behaviour,
+}
+
+/// Behaviour of a client asking an AutoNAT server to dial back its observed address.
+#[derive(NetworkBehaviour)]
+struct AutonatBehaviour {
+    identify: identify::Behaviour,
+    autonat: autonat::Behaviour,
 }
 
 impl TryFrom<Command> for Operation {
@@ -25,16 +67,62 @@ impl TryFrom<Command> for Operation {
         match value {
             Command::P2pPing(args) => Ok(Operation::Ping(args)),
             Command::P2pIdentify(args) => Ok(Operation::Identify(args)),
+            Command::P2pFindPeer(args) => Ok(Operation::FindPeer(args)),
+            Command::P2pDhtProviders(args) => Ok(Operation::DhtProviders(args)),
+            Command::P2pGossipsubListen(args) => Ok(Operation::GossipsubListen(args)),
+            Command::P2pListen(args) => Ok(Operation::Listen(args)),
+            Command::P2pDiscover(args) => Ok(Operation::Discover(args)),
+            Command::P2pProbe(args) => Ok(Operation::Probe(args)),
+            Command::P2pConnectTime(args) => Ok(Operation::ConnectTime(args)),
+            Command::P2pGetBlock(args) => Ok(Operation::GetBlock(args)),
+            Command::P2pAutonat(args) => Ok(Operation::Autonat(args)),
             _ => Err(value),
         }
     }
 }
 
-pub async fn run(op: Operation, _stdin: impl AsyncRead, stdout: impl AsyncWrite) -> Result<()> {
+pub async fn run(
+    op: Operation,
+    format: OutputFormat,
+    mut rng: StdRng,
+    _stdin: impl AsyncRead,
+    stdout: impl AsyncWrite,
+) -> Result<()> {
     pin_mut!(stdout);
     match op {
         Operation::Ping(args) => {
-            let local_key = identity::Keypair::generate_ed25519();
+            let ping_format = match (format, args.format) {
+                (OutputFormat::Json, PingFormat::Csv) => {
+                    bail!("--json cannot be combined with --format csv")
+                }
+                (OutputFormat::Json, _) => PingFormat::Json,
+                (OutputFormat::Text, ping_format) => ping_format,
+            };
+            let local_key = local_keypair(&args.swarm, &mut rng).await?;
+            let target = args.peer_addr.parse::<PeerId>().ok();
+            let addresses = if let Some(target) = target {
+                if args.bootstrap.is_empty() {
+                    bail!("--bootstrap is required to resolve the addresses of peer {target}");
+                }
+                let addresses = find_peer(
+                    local_key.clone(),
+                    args.swarm.transport,
+                    target,
+                    args.bootstrap,
+                    args.dht_protocol,
+                    args.dht_timeout,
+                )
+                .await?;
+                if addresses.is_empty() {
+                    bail!("no addresses found for {target}");
+                }
+                for addr in &addresses {
+                    log::info!("resolved {target} to {addr}");
+                }
+                addresses
+            } else {
+                Vec::new()
+            };
             let mut swarm = p2p_swarm(
                 local_key,
                 ping::Behaviour::new(
@@ -42,50 +130,118 @@ pub async fn run(op: Operation, _stdin: impl AsyncRead, stdout: impl AsyncWrite)
                         .with_interval(Duration::from_secs(args.interval as u64))
                         .with_timeout(Duration::from_secs(args.timeout as u64)),
                 ),
+                args.swarm.transport,
+                0,
             )
             .await?;
-            let remote: Multiaddr = args.peer_addr.parse()?;
-            swarm.dial(remote)?;
+            match target {
+                Some(target) => {
+                    swarm.dial(DialOpts::peer_id(target).addresses(addresses).build())?
+                }
+                None => dial(&mut swarm, &args.peer_addr).await?,
+            }
 
-            let mut count = 0;
+            let mut sent = 0;
+            let mut durations = Vec::new();
+            let mut responses = Vec::new();
+            if let PingFormat::Csv = ping_format {
+                stdout.write_all(b"seq,peer,rtt_ms\n").await?;
+            }
+            // Stop on Ctrl-C so the summary of the pings so far is still printed. The listener
+            // is created once so a signal between loop iterations is not lost.
+            let ctrl_c = signal::ctrl_c();
+            pin_mut!(ctrl_c);
             loop {
-                match swarm.select_next_some().await {
+                let event = tokio::select! {
+                    event = swarm.select_next_some() => event,
+                    _ = &mut ctrl_c => break,
+                };
+                match event {
+                    SwarmEvent::ConnectionEstablished {
+                        peer_id, endpoint, ..
+                    } => {
+                        log::info!(
+                            "connected to {peer_id} at {}",
+                            endpoint.get_remote_address()
+                        );
+                    }
                     SwarmEvent::OutgoingConnectionError { peer_id, error, .. } => {
-                        stdout
-                            .write_all(
-                                format!("failed to connect to {peer_id:?}: {error}\n").as_bytes(),
-                            )
-                            .await?;
+                        let msg = format!("failed to connect to {peer_id:?}: {error}");
+                        match ping_format {
+                            PingFormat::Text => {
+                                stdout.write_all(format!("{msg}\n").as_bytes()).await?
+                            }
+                            PingFormat::Json | PingFormat::Csv => log::warn!("{msg}"),
+                        }
                         break;
                     }
                     SwarmEvent::Behaviour(ping::Event { peer, result, .. }) => {
+                        sent += 1;
                         match result {
                             Ok(duration) => {
-                                stdout
-                                    .write_all(
-                                        format!("response from {peer} in {duration:?}\n")
-                                            .as_bytes(),
-                                    )
-                                    .await?
+                                durations.push(duration);
+                                match ping_format {
+                                    PingFormat::Text => {
+                                        stdout
+                                            .write_all(
+                                                format!("response from {peer} in {duration:?}\n")
+                                                    .as_bytes(),
+                                            )
+                                            .await?
+                                    }
+                                    PingFormat::Csv => {
+                                        stdout
+                                            .write_all(
+                                                format!("{sent},{peer},{}\n", millis(duration))
+                                                    .as_bytes(),
+                                            )
+                                            .await?
+                                    }
+                                    PingFormat::Json => responses.push(PingResponse {
+                                        seq: sent,
+                                        peer: peer.to_string(),
+                                        rtt_ms: millis(duration),
+                                    }),
+                                }
                             }
                             Err(err) => {
-                                stdout
-                                    .write_all(format!("ping failed {err}\n").as_bytes())
-                                    .await?;
+                                match ping_format {
+                                    PingFormat::Text => {
+                                        stdout
+                                            .write_all(format!("ping failed {err}\n").as_bytes())
+                                            .await?
+                                    }
+                                    PingFormat::Json | PingFormat::Csv => {
+                                        log::warn!("ping failed {err}")
+                                    }
+                                }
                                 break;
                             }
                         };
-                        count += 1;
-                        if count >= args.count {
+                        if sent >= args.count {
                             break;
                         }
                     }
                     _ => {}
                 }
             }
+            match ping_format {
+                PingFormat::Text | PingFormat::Csv => {
+                    stdout
+                        .write_all(ping_summary(&args.peer_addr, sent, &durations).as_bytes())
+                        .await?
+                }
+                PingFormat::Json => {
+                    let out = serde_json::to_string(&PingReport {
+                        responses,
+                        summary: PingSummary::new(sent, &durations),
+                    })?;
+                    stdout.write_all(format!("{out}\n").as_bytes()).await?
+                }
+            }
         }
         Operation::Identify(args) => {
-            let local_key = identity::Keypair::generate_ed25519();
+            let local_key = local_keypair(&args.swarm, &mut rng).await?;
             let public_key = local_key.public();
             let mut swarm = p2p_swarm(
                 local_key,
@@ -93,13 +249,26 @@ pub async fn run(op: Operation, _stdin: impl AsyncRead, stdout: impl AsyncWrite)
                     "/ipfs/id/1.0.0".to_string(),
                     public_key,
                 )),
+                args.swarm.transport,
+                0,
             )
             .await?;
-            let remote: Multiaddr = args.peer_addr.parse()?;
-            swarm.dial(remote)?;
+            dial(&mut swarm, &args.peer_addr).await?;
 
+            let deadline = Instant::now() + Duration::from_secs(args.timeout as u64);
             loop {
-                match swarm.select_next_some().await {
+                let Ok(event) = timeout_at(deadline, swarm.select_next_some()).await else {
+                    bail!("identify timed out after {}s", args.timeout);
+                };
+                match event {
+                    SwarmEvent::ConnectionEstablished {
+                        peer_id, endpoint, ..
+                    } => {
+                        log::info!(
+                            "connected to {peer_id} at {}",
+                            endpoint.get_remote_address()
+                        );
+                    }
                     SwarmEvent::OutgoingConnectionError { peer_id, error, .. } => {
                         stdout
                             .write_all(
@@ -110,40 +279,8 @@ pub async fn run(op: Operation, _stdin: impl AsyncRead, stdout: impl AsyncWrite)
                     }
                     SwarmEvent::Behaviour(event) => match event {
                         identify::Event::Received { peer_id, info } => {
-                            let public_key_type = info.public_key.key_type();
-                            let protocol_version = info.protocol_version;
-                            let agent_version = info.agent_version;
-                            let listen_addrs = info
-                                .listen_addrs
-                                .iter()
-                                .map(ToString::to_string)
-                                .collect::<Vec<String>>()
-                                .join("\n\t");
-                            let mut protocols = info
-                                .protocols
-                                .iter()
-                                .map(ToString::to_string)
-                                .collect::<Vec<String>>();
-                            protocols.sort();
-                            let protocols = protocols.join("\n\t");
-                            let observed_address = info.observed_addr;
-                            stdout
-                                .write_all(
-                                    format!(
-                                        "Peer: {peer_id}
-Public Key Type: {public_key_type}
-Protocol Version: {protocol_version}
-Agent Version: {agent_version}
-Observed Address: {observed_address}
-Listen Addresses:
-\t{listen_addrs}
-Protocols:
-\t{protocols}
-"
-                                    )
-                                    .as_bytes(),
-                                )
-                                .await?;
+                            let out = fmt_identify(&identify_info(peer_id, info), format)?;
+                            stdout.write_all(format!("{out}\n").as_bytes()).await?;
                             break;
                         }
                         identify::Event::Error { error, .. } => {
@@ -159,33 +296,1032 @@ Protocols:
                 }
             }
         }
+        Operation::FindPeer(args) => {
+            let target: PeerId = args
+                .peer_id
+                .parse()
+                .with_context(|| format!("failed to parse peer ID '{}'", args.peer_id))?;
+            let local_key = local_keypair(&args.swarm, &mut rng).await?;
+            let addresses: Vec<String> = find_peer(
+                local_key,
+                args.swarm.transport,
+                target,
+                args.bootstrap,
+                args.protocol,
+                args.timeout,
+            )
+            .await?
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+            if addresses.is_empty() {
+                stdout
+                    .write_all(format!("no addresses found for {target}\n").as_bytes())
+                    .await?;
+            } else {
+                stdout
+                    .write_all(
+                        format!("Peer: {target}\nAddresses:\n\t{}\n", addresses.join("\n\t"))
+                            .as_bytes(),
+                    )
+                    .await?;
+            }
+        }
+        Operation::DhtProviders(args) => {
+            let local_key = local_keypair(&args.swarm, &mut rng).await?;
+            let behaviour =
+                kad_behaviour(local_key.public().to_peer_id(), args.protocol, args.timeout)?;
+            let mut swarm = p2p_swarm(local_key, behaviour, args.swarm.transport, 0).await?;
+            for addr in args.bootstrap {
+                let addr: Multiaddr = addr
+                    .parse()
+                    .with_context(|| format!("failed to parse bootstrap address '{addr}'"))?;
+                swarm
+                    .behaviour_mut()
+                    .add_address(&peer_id_from_addr(&addr)?, addr);
+            }
+            swarm.behaviour_mut().bootstrap()?;
+            // Providers are keyed by the multihash so any CID version or codec finds them.
+            swarm
+                .behaviour_mut()
+                .get_providers(kad::RecordKey::new(&args.cid.hash().to_bytes()));
+
+            let mut providers = BTreeSet::new();
+            let mut known_addresses: HashMap<PeerId, Vec<Multiaddr>> = HashMap::new();
+            loop {
+                match swarm.select_next_some().await {
+                    SwarmEvent::Behaviour(kad::Event::RoutingUpdated {
+                        peer, addresses, ..
+                    }) => {
+                        known_addresses.insert(peer, addresses.into_vec());
+                    }
+                    SwarmEvent::Behaviour(kad::Event::OutboundQueryProgressed {
+                        result: kad::QueryResult::GetProviders(result),
+                        step,
+                        ..
+                    }) => {
+                        match result {
+                            Ok(kad::GetProvidersOk::FoundProviders {
+                                providers: found, ..
+                            }) => providers.extend(found),
+                            Ok(kad::GetProvidersOk::FinishedWithNoAdditionalRecord { .. }) => {}
+                            Err(err) => {
+                                stdout
+                                    .write_all(format!("DHT query failed: {err}\n").as_bytes())
+                                    .await?;
+                            }
+                        }
+                        if step.last {
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            if providers.is_empty() {
+                stdout
+                    .write_all(format!("no providers found for {}\n", args.cid).as_bytes())
+                    .await?;
+            }
+            for provider in providers {
+                let addresses = known_addresses
+                    .get(&provider)
+                    .map(|addresses| {
+                        addresses
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join("\n\t")
+                    })
+                    .unwrap_or_default();
+                stdout
+                    .write_all(
+                        format!("Provider: {provider}\nAddresses:\n\t{addresses}\n").as_bytes(),
+                    )
+                    .await?;
+            }
+        }
+        Operation::GossipsubListen(args) => {
+            let local_key = local_keypair(&args.swarm, &mut rng).await?;
+            let mut behaviour = gossipsub::Behaviour::new(
+                gossipsub::MessageAuthenticity::Signed(local_key.clone()),
+                gossipsub::Config::default(),
+            )
+            .map_err(|err| anyhow!("failed to create gossipsub behaviour: {err}"))?;
+            behaviour.subscribe(&gossipsub::IdentTopic::new(args.topic))?;
+            let mut swarm = p2p_swarm(local_key, behaviour, args.swarm.transport, 0).await?;
+            dial(&mut swarm, &args.peer_addr).await?;
+
+            loop {
+                match swarm.select_next_some().await {
+                    SwarmEvent::OutgoingConnectionError { peer_id, error, .. } => {
+                        stdout
+                            .write_all(
+                                format!("failed to connect to {peer_id:?}: {error}\n").as_bytes(),
+                            )
+                            .await?;
+                        break;
+                    }
+                    SwarmEvent::Behaviour(gossipsub::Event::Message {
+                        propagation_source,
+                        message,
+                        ..
+                    }) => {
+                        let source = message
+                            .source
+                            .map(|peer_id| peer_id.to_string())
+                            .unwrap_or_else(|| "unknown".to_string());
+                        stdout
+                            .write_all(
+                                format!(
+                                    "Source: {source}
+Propagation Source: {propagation_source}
+Topic: {}
+Data (hex): {}
+Data (utf8): {}
+",
+                                    message.topic,
+                                    hex::encode(&message.data),
+                                    String::from_utf8_lossy(&message.data),
+                                )
+                                .as_bytes(),
+                            )
+                            .await?;
+                    }
+                    SwarmEvent::Behaviour(gossipsub::Event::Subscribed { peer_id, topic }) => {
+                        log::info!("{peer_id} subscribed to {topic}");
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Operation::Listen(args) => {
+            let local_key = local_keypair(&args.swarm, &mut rng).await?;
+            let local_peer_id = local_key.public().to_peer_id();
+            let behaviour = PeerBehaviour {
+                ping: ping::Behaviour::default(),
+                identify: identify::Behaviour::new(identify::Config::new(
+                    "/ipfs/id/1.0.0".to_string(),
+                    local_key.public(),
+                )),
+            };
+            let mut swarm =
+                p2p_swarm(local_key, behaviour, args.swarm.transport, args.port).await?;
+
+            loop {
+                let out = match swarm.select_next_some().await {
+                    SwarmEvent::NewListenAddr { address, .. } => {
+                        format!("listening on {address}/p2p/{local_peer_id}")
+                    }
+                    SwarmEvent::ConnectionEstablished {
+                        peer_id, endpoint, ..
+                    } if endpoint.is_listener() => format!(
+                        "inbound connection from {peer_id} at {}",
+                        endpoint.get_remote_address()
+                    ),
+                    SwarmEvent::ConnectionClosed { peer_id, cause, .. } => match cause {
+                        Some(cause) => format!("connection to {peer_id} closed: {cause}"),
+                        None => format!("connection to {peer_id} closed"),
+                    },
+                    SwarmEvent::Behaviour(PeerBehaviourEvent::Identify(
+                        identify::Event::Received { peer_id, info },
+                    )) => format!(
+                        "identified {peer_id} agent version {} protocol version {}",
+                        info.agent_version, info.protocol_version
+                    ),
+                    SwarmEvent::Behaviour(PeerBehaviourEvent::Identify(
+                        identify::Event::Sent { peer_id },
+                    )) => format!("sent identify to {peer_id}"),
+                    _ => continue,
+                };
+                stdout.write_all(format!("{out}\n").as_bytes()).await?;
+            }
+        }
+        Operation::Discover(args) => {
+            let local_key = local_keypair(&args.swarm, &mut rng).await?;
+            let behaviour = mdns::tokio::Behaviour::new(
+                mdns::Config::default(),
+                local_key.public().to_peer_id(),
+            )?;
+            let mut swarm = p2p_swarm(local_key, behaviour, args.swarm.transport, 0).await?;
+
+            loop {
+                match swarm.select_next_some().await {
+                    SwarmEvent::Behaviour(mdns::Event::Discovered(peers)) => {
+                        for (peer_id, addr) in peers {
+                            stdout
+                                .write_all(format!("discovered {peer_id} at {addr}\n").as_bytes())
+                                .await?;
+                        }
+                    }
+                    SwarmEvent::Behaviour(mdns::Event::Expired(peers)) => {
+                        for (peer_id, addr) in peers {
+                            stdout
+                                .write_all(format!("expired {peer_id} at {addr}\n").as_bytes())
+                                .await?;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Operation::Probe(args) => {
+            let local_key = local_keypair(&args.swarm, &mut rng).await?;
+            let behaviour = PeerBehaviour {
+                ping: ping::Behaviour::new(
+                    ping::Config::new()
+                        .with_interval(Duration::from_secs(args.interval as u64))
+                        .with_timeout(Duration::from_secs(args.timeout as u64)),
+                ),
+                identify: identify::Behaviour::new(identify::Config::new(
+                    "/ipfs/id/1.0.0".to_string(),
+                    local_key.public(),
+                )),
+            };
+            let mut swarm = p2p_swarm(local_key, behaviour, args.swarm.transport, 0).await?;
+            dial(&mut swarm, &args.peer_addr).await?;
+
+            let mut identified = false;
+            let mut peer_info = None;
+            let mut sent = 0;
+            let mut durations = Vec::new();
+            let mut responses = Vec::new();
+            let ctrl_c = signal::ctrl_c();
+            pin_mut!(ctrl_c);
+            loop {
+                let event = tokio::select! {
+                    event = swarm.select_next_some() => event,
+                    _ = &mut ctrl_c => break,
+                };
+                match event {
+                    SwarmEvent::OutgoingConnectionError { peer_id, error, .. } => {
+                        let msg = format!("failed to connect to {peer_id:?}: {error}");
+                        match format {
+                            OutputFormat::Text => {
+                                stdout.write_all(format!("{msg}\n").as_bytes()).await?
+                            }
+                            OutputFormat::Json => log::warn!("{msg}"),
+                        }
+                        break;
+                    }
+                    SwarmEvent::Behaviour(PeerBehaviourEvent::Identify(
+                        identify::Event::Received { peer_id, info },
+                    )) => {
+                        let info = identify_info(peer_id, info);
+                        match format {
+                            OutputFormat::Text => {
+                                let out = fmt_identify(&info, format)?;
+                                stdout.write_all(format!("{out}\n").as_bytes()).await?;
+                            }
+                            OutputFormat::Json => peer_info = Some(info),
+                        }
+                        identified = true;
+                    }
+                    SwarmEvent::Behaviour(PeerBehaviourEvent::Identify(
+                        identify::Event::Error { error, .. },
+                    )) => {
+                        let msg = format!("Error getting peer identity: {error}");
+                        match format {
+                            OutputFormat::Text => {
+                                stdout.write_all(format!("{msg}\n").as_bytes()).await?
+                            }
+                            OutputFormat::Json => log::warn!("{msg}"),
+                        }
+                        identified = true;
+                    }
+                    SwarmEvent::Behaviour(PeerBehaviourEvent::Ping(ping::Event {
+                        peer,
+                        result,
+                        ..
+                    })) => {
+                        sent += 1;
+                        match result {
+                            Ok(duration) => {
+                                durations.push(duration);
+                                match format {
+                                    OutputFormat::Text => {
+                                        stdout
+                                            .write_all(
+                                                format!("response from {peer} in {duration:?}\n")
+                                                    .as_bytes(),
+                                            )
+                                            .await?
+                                    }
+                                    OutputFormat::Json => responses.push(PingResponse {
+                                        seq: sent,
+                                        peer: peer.to_string(),
+                                        rtt_ms: millis(duration),
+                                    }),
+                                }
+                            }
+                            Err(err) => {
+                                match format {
+                                    OutputFormat::Text => {
+                                        stdout
+                                            .write_all(format!("ping failed {err}\n").as_bytes())
+                                            .await?
+                                    }
+                                    OutputFormat::Json => log::warn!("ping failed {err}"),
+                                }
+                                break;
+                            }
+                        };
+                    }
+                    _ => {}
+                }
+                if identified && sent >= args.count {
+                    break;
+                }
+            }
+            match format {
+                OutputFormat::Text => {
+                    stdout
+                        .write_all(ping_summary(&args.peer_addr, sent, &durations).as_bytes())
+                        .await?
+                }
+                OutputFormat::Json => {
+                    let out = serde_json::to_string(&ProbeReport {
+                        identify: peer_info,
+                        responses,
+                        summary: PingSummary::new(sent, &durations),
+                    })?;
+                    stdout.write_all(format!("{out}\n").as_bytes()).await?
+                }
+            }
+        }
+        Operation::ConnectTime(args) => {
+            let local_key = local_keypair(&args.swarm, &mut rng).await?;
+            let mut swarm = p2p_swarm(local_key, dummy::Behaviour, args.swarm.transport, 0).await?;
+            let remote: Multiaddr = args
+                .peer_addr
+                .parse()
+                .with_context(|| format!("failed to parse peer address '{}'", args.peer_addr))?;
+
+            let mut attempted = 0;
+            let mut durations = Vec::new();
+            // Stop on Ctrl-C so the summary of the connections so far is still printed.
+            let ctrl_c = signal::ctrl_c();
+            pin_mut!(ctrl_c);
+            'connect: for iteration in 1..=args.iterations {
+                attempted = iteration;
+                let start = Instant::now();
+                swarm.dial(remote.clone())?;
+                loop {
+                    let event = tokio::select! {
+                        event = swarm.select_next_some() => event,
+                        _ = &mut ctrl_c => break 'connect,
+                    };
+                    match event {
+                        SwarmEvent::ConnectionEstablished { peer_id, .. } => {
+                            let duration = start.elapsed();
+                            durations.push(duration);
+                            stdout
+                                .write_all(
+                                    format!("connection {iteration} established in {duration:?}\n")
+                                        .as_bytes(),
+                                )
+                                .await?;
+                            // Ignoring the error as the connection may already be closed.
+                            let _ = swarm.disconnect_peer_id(peer_id);
+                        }
+                        SwarmEvent::ConnectionClosed { .. } => break,
+                        SwarmEvent::OutgoingConnectionError { peer_id, error, .. } => {
+                            stdout
+                                .write_all(
+                                    format!(
+                                        "connection {iteration} failed to connect to {peer_id:?}: {error}\n"
+                                    )
+                                    .as_bytes(),
+                                )
+                                .await?;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            let mut summary = format!(
+                "--- {} connect statistics ---\n{} connections attempted, {} established\n",
+                args.peer_addr,
+                attempted,
+                durations.len()
+            );
+            if let Some(stats) = DurationStats::from_durations(&durations) {
+                summary.push_str(&format!(
+                    "connect min/avg/max/stddev = {:?}/{:?}/{:?}/{:?}\n",
+                    stats.min, stats.avg, stats.max, stats.stddev,
+                ));
+            }
+            stdout.write_all(summary.as_bytes()).await?;
+        }
+        Operation::Autonat(args) => {
+            let server_addr: Multiaddr = args.server_addr.parse().with_context(|| {
+                format!("failed to parse server address '{}'", args.server_addr)
+            })?;
+            let server = peer_id_from_addr(&server_addr)?;
+            let local_key = local_keypair(&args.swarm, &mut rng).await?;
+            let mut behaviour = AutonatBehaviour {
+                identify: identify::Behaviour::new(identify::Config::new(
+                    "/ipfs/id/1.0.0".to_string(),
+                    local_key.public(),
+                )),
+                autonat: autonat::Behaviour::new(
+                    local_key.public().to_peer_id(),
+                    autonat::Config {
+                        // Probe as soon as the server has told us our observed address.
+                        boot_delay: Duration::from_secs(1),
+                        ..Default::default()
+                    },
+                ),
+            };
+            behaviour
+                .autonat
+                .add_server(server, Some(server_addr.clone()));
+            let mut swarm = p2p_swarm(local_key, behaviour, args.swarm.transport, 0).await?;
+            swarm.dial(server_addr)?;
+
+            let mut observed_addr = None;
+            let deadline = Instant::now() + Duration::from_secs(args.timeout as u64);
+            let status = loop {
+                let Ok(event) = timeout_at(deadline, swarm.select_next_some()).await else {
+                    log::warn!("timed out after {}s waiting for a probe", args.timeout);
+                    break autonat::NatStatus::Unknown;
+                };
+                match event {
+                    SwarmEvent::OutgoingConnectionError { peer_id, error, .. } => {
+                        bail!("failed to connect to {peer_id:?}: {error}");
+                    }
+                    SwarmEvent::Behaviour(AutonatBehaviourEvent::Identify(
+                        identify::Event::Received { peer_id, info },
+                    )) if peer_id == server => {
+                        observed_addr = Some(info.observed_addr);
+                    }
+                    SwarmEvent::Behaviour(AutonatBehaviourEvent::Autonat(
+                        autonat::Event::OutboundProbe(autonat::OutboundProbeEvent::Error {
+                            error,
+                            ..
+                        }),
+                    )) => {
+                        log::warn!("probe failed: {error:?}");
+                    }
+                    SwarmEvent::Behaviour(AutonatBehaviourEvent::Autonat(
+                        autonat::Event::StatusChanged { new, .. },
+                    )) => break new,
+                    _ => {}
+                }
+            };
+            let (status, observed_addr) = match status {
+                autonat::NatStatus::Public(addr) => ("Public", Some(addr)),
+                autonat::NatStatus::Private => ("Private", observed_addr),
+                autonat::NatStatus::Unknown => ("Unknown", observed_addr),
+            };
+            stdout
+                .write_all(
+                    format!(
+                        "NAT Status: {status}\nObserved Address: {}\n",
+                        observed_addr
+                            .map(|addr| addr.to_string())
+                            .unwrap_or_else(|| "unknown".to_string())
+                    )
+                    .as_bytes(),
+                )
+                .await?;
+        }
+        Operation::GetBlock(args) => {
+            let local_key = local_keypair(&args.swarm, &mut rng).await?;
+            let behaviour = bitswap::Behaviour::new(args.cid)?;
+            let mut swarm = p2p_swarm(local_key, behaviour, args.swarm.transport, 0).await?;
+            dial(&mut swarm, &args.peer_addr).await?;
+
+            let deadline = Instant::now() + Duration::from_secs(args.timeout as u64);
+            loop {
+                let Ok(event) = timeout_at(deadline, swarm.select_next_some()).await else {
+                    bail!(
+                        "timed out after {}s waiting for block {}",
+                        args.timeout,
+                        args.cid
+                    );
+                };
+                match event {
+                    SwarmEvent::OutgoingConnectionError { peer_id, error, .. } => {
+                        bail!("failed to connect to {peer_id:?}: {error}")
+                    }
+                    SwarmEvent::Behaviour(bitswap::Event::Block { data, .. }) => {
+                        stdout.write_all(&data).await?;
+                        break;
+                    }
+                    SwarmEvent::Behaviour(bitswap::Event::DontHave { peer }) => {
+                        bail!("{peer} does not have block {}", args.cid)
+                    }
+                    SwarmEvent::Behaviour(bitswap::Event::Error { peer, error }) => {
+                        bail!("failed to request block from {peer}: {error}")
+                    }
+                    _ => {}
+                }
+            }
+        }
     }
     Ok(())
 }
-async fn p2p_swarm<B>(local_key: Keypair, behaviour: B) -> Result<Swarm<B>>
+
+/// Collect the identify info received from a peer.
+fn identify_info(peer_id: PeerId, info: identify::Info) -> IdentifyInfo {
+    let mut protocols = info
+        .protocols
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>();
+    protocols.sort();
+    IdentifyInfo {
+        peer_id: peer_id.to_string(),
+        public_key_type: info.public_key.key_type().to_string(),
+        protocol_version: info.protocol_version,
+        agent_version: info.agent_version,
+        observed_address: info.observed_addr.to_string(),
+        listen_addresses: info.listen_addrs.iter().map(ToString::to_string).collect(),
+        protocols,
+    }
+}
+
+/// Format the identify info received from a peer.
+fn fmt_identify(info: &IdentifyInfo, format: OutputFormat) -> Result<String> {
+    Ok(if format == OutputFormat::Json {
+        serde_json::to_string(info)?
+    } else {
+        format!(
+            "Peer: {}
+Public Key Type: {}
+Protocol Version: {}
+Agent Version: {}
+Observed Address: {}
+Listen Addresses:
+\t{}
+Protocols:
+\t{}",
+            info.peer_id,
+            info.public_key_type,
+            info.protocol_version,
+            info.agent_version,
+            info.observed_address,
+            info.listen_addresses.join("\n\t"),
+            info.protocols.join("\n\t"),
+        )
+    })
+}
+
+/// Identify info received from a peer.
+#[derive(Serialize)]
+struct IdentifyInfo {
+    peer_id: String,
+    public_key_type: String,
+    protocol_version: String,
+    agent_version: String,
+    observed_address: String,
+    listen_addresses: Vec<String>,
+    protocols: Vec<String>,
+}
+
+/// Statistics of a set of measured durations.
+struct DurationStats {
+    min: Duration,
+    avg: Duration,
+    max: Duration,
+    stddev: Duration,
+}
+
+impl DurationStats {
+    fn from_durations(durations: &[Duration]) -> Option<Self> {
+        let min = *durations.iter().min()?;
+        let max = *durations.iter().max()?;
+        let secs = durations
+            .iter()
+            .map(Duration::as_secs_f64)
+            .collect::<Vec<f64>>();
+        let avg = secs.iter().sum::<f64>() / secs.len() as f64;
+        let variance = secs.iter().map(|s| (s - avg).powi(2)).sum::<f64>() / secs.len() as f64;
+        Some(Self {
+            min,
+            avg: Duration::from_secs_f64(avg),
+            max,
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        })
+    }
+}
+
+fn loss_percent(sent: usize, received: usize) -> f64 {
+    if sent == 0 {
+        0.0
+    } else {
+        (sent - received) as f64 / sent as f64 * 100.0
+    }
+}
+
+/// Format ping statistics in the style of the Unix ping summary.
+fn ping_summary(peer_addr: &str, sent: usize, durations: &[Duration]) -> String {
+    let received = durations.len();
+    let loss = loss_percent(sent, received);
+    let mut summary = format!(
+        "--- {peer_addr} ping statistics ---\n{sent} pings transmitted, {received} received, {loss:.1}% loss\n"
+    );
+    if let Some(rtt) = DurationStats::from_durations(durations) {
+        summary.push_str(&format!(
+            "rtt min/avg/max/stddev = {:?}/{:?}/{:?}/{:?}\n",
+            rtt.min, rtt.avg, rtt.max, rtt.stddev,
+        ));
+    }
+    summary
+}
+
+/// Responses and summary of p2p-ping with the JSON format.
+#[derive(Serialize)]
+struct PingReport {
+    responses: Vec<PingResponse>,
+    summary: PingSummary,
+}
+
+/// Identify info, responses and summary of p2p-probe with the JSON format.
+#[derive(Serialize)]
+struct ProbeReport {
+    identify: Option<IdentifyInfo>,
+    responses: Vec<PingResponse>,
+    summary: PingSummary,
+}
+
+/// A ping response with its round trip time in milliseconds.
+#[derive(Serialize)]
+struct PingResponse {
+    seq: usize,
+    peer: String,
+    rtt_ms: f64,
+}
+
+/// Ping statistics with round trip times in milliseconds, rtt is None without responses.
+#[derive(Serialize)]
+struct PingSummary {
+    transmitted: usize,
+    received: usize,
+    loss_percent: f64,
+    #[serde(flatten)]
+    rtt: Option<RttSummary>,
+}
+
+#[derive(Serialize)]
+struct RttSummary {
+    rtt_min_ms: f64,
+    rtt_avg_ms: f64,
+    rtt_max_ms: f64,
+    rtt_stddev_ms: f64,
+}
+
+impl PingSummary {
+    fn new(sent: usize, durations: &[Duration]) -> Self {
+        let received = durations.len();
+        Self {
+            transmitted: sent,
+            received,
+            loss_percent: loss_percent(sent, received),
+            rtt: DurationStats::from_durations(durations).map(|rtt| RttSummary {
+                rtt_min_ms: millis(rtt.min),
+                rtt_avg_ms: millis(rtt.avg),
+                rtt_max_ms: millis(rtt.max),
+                rtt_stddev_ms: millis(rtt.stddev),
+            }),
+        }
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Load the local identity from a key file, otherwise generate a new random identity.
+/// When a key file is given but does not exist, the new key is saved to it.
+async fn local_keypair(args: &SwarmArgs, rng: &mut impl Rng) -> Result<Keypair> {
+    let mut secret = [0u8; 32];
+    if let Some(path) = args.key_file.as_ref().filter(|path| path.exists()) {
+        let key = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("failed to read key file {}", path.display()))?;
+        hex::decode_to_slice(key.trim(), &mut secret).with_context(|| {
+            format!(
+                "key file {} is not a hex encoded ed25519 key",
+                path.display()
+            )
+        })?;
+    } else {
+        rng.fill(&mut secret);
+        if let Some(path) = &args.key_file {
+            let mut options = tokio::fs::OpenOptions::new();
+            // Never overwrite a key created concurrently and keep the private key readable
+            // only by its owner.
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            options.mode(0o600);
+            let mut file = options
+                .open(path)
+                .await
+                .with_context(|| format!("failed to create key file {}", path.display()))?;
+            file.write_all(format!("{}\n", hex::encode(secret)).as_bytes())
+                .await
+                .with_context(|| format!("failed to write key file {}", path.display()))?;
+            file.flush()
+                .await
+                .with_context(|| format!("failed to write key file {}", path.display()))?;
+        }
+    }
+    let keypair = identity::Keypair::ed25519_from_bytes(secret)?;
+    log::info!("Local Peer ID: {}", keypair.public().to_peer_id());
+    Ok(keypair)
+}
+
+async fn p2p_swarm<B>(
+    local_key: Keypair,
+    behaviour: B,
+    transport: Transport,
+    port: u16,
+) -> Result<Swarm<B>>
 where
     B: NetworkBehaviour,
 {
-    let mut swarm = SwarmBuilder::with_existing_identity(local_key)
-        .with_tokio()
-        .with_tcp(
-            tcp::Config::default(),
-            noise::Config::new,
-            yamux::Config::default,
-        )?
-        .with_quic()
-        .with_dns()?
-        .with_websocket(
-            (tls::Config::new, noise::Config::new),
-            yamux::Config::default,
-        )
-        .await?
-        .with_behaviour(|_| behaviour)?
-        .with_swarm_config(|config| config.with_idle_connection_timeout(Duration::from_secs(30)))
-        .build();
-
-    // Tell the swarm to listen on all interfaces and a random, OS-assigned
-    // port.
-    swarm.listen_on("/ip4/0.0.0.0/tcp/0".parse()?)?;
+    let builder = SwarmBuilder::with_existing_identity(local_key).with_tokio();
+    let swarm_config = |config: libp2p::swarm::Config| {
+        config.with_idle_connection_timeout(Duration::from_secs(30))
+    };
+    let (mut swarm, listen_addrs) = match transport {
+        Transport::All => (
+            builder
+                .with_tcp(
+                    tcp::Config::default(),
+                    noise::Config::new,
+                    yamux::Config::default,
+                )?
+                .with_quic()
+                .with_dns()?
+                .with_websocket(
+                    (tls::Config::new, noise::Config::new),
+                    yamux::Config::default,
+                )
+                .await?
+                .with_behaviour(|_| behaviour)?
+                .with_swarm_config(swarm_config)
+                .build(),
+            vec![
+                format!("/ip4/0.0.0.0/tcp/{port}"),
+                format!("/ip4/0.0.0.0/udp/{port}/quic-v1"),
+            ],
+        ),
+        Transport::Tcp => (
+            builder
+                .with_tcp(
+                    tcp::Config::default(),
+                    noise::Config::new,
+                    yamux::Config::default,
+                )?
+                .with_dns()?
+                .with_behaviour(|_| behaviour)?
+                .with_swarm_config(swarm_config)
+                .build(),
+            vec![format!("/ip4/0.0.0.0/tcp/{port}")],
+        ),
+        Transport::Quic => (
+            builder
+                .with_quic()
+                .with_dns()?
+                .with_behaviour(|_| behaviour)?
+                .with_swarm_config(swarm_config)
+                .build(),
+            vec![format!("/ip4/0.0.0.0/udp/{port}/quic-v1")],
+        ),
+        Transport::Ws => (
+            builder
+                .with_websocket(
+                    (tls::Config::new, noise::Config::new),
+                    yamux::Config::default,
+                )
+                .await?
+                .with_behaviour(|_| behaviour)?
+                .with_swarm_config(swarm_config)
+                .build(),
+            vec![format!("/ip4/0.0.0.0/tcp/{port}/ws")],
+        ),
+    };
+
+    // Tell the swarm to listen on all interfaces, a port of 0 uses a random,
+    // OS-assigned port.
+    for addr in listen_addrs {
+        swarm.listen_on(addr.parse()?)?;
+    }
     Ok(swarm)
 }
+
+/// Find the addresses of a peer by querying the DHT through the bootstrap peers.
+async fn find_peer(
+    local_key: Keypair,
+    transport: Transport,
+    target: PeerId,
+    bootstrap: Vec<String>,
+    protocol: String,
+    timeout: u32,
+) -> Result<Vec<Multiaddr>> {
+    let behaviour = kad_behaviour(local_key.public().to_peer_id(), protocol, timeout)?;
+    let mut swarm = p2p_swarm(local_key, behaviour, transport, 0).await?;
+    for addr in bootstrap {
+        let addr: Multiaddr = addr
+            .parse()
+            .with_context(|| format!("failed to parse bootstrap address '{addr}'"))?;
+        swarm
+            .behaviour_mut()
+            .add_address(&peer_id_from_addr(&addr)?, addr);
+    }
+    swarm.behaviour_mut().get_closest_peers(target);
+
+    let mut addresses = Vec::new();
+    loop {
+        match swarm.select_next_some().await {
+            SwarmEvent::Behaviour(kad::Event::RoutingUpdated {
+                peer,
+                addresses: peer_addresses,
+                ..
+            }) if peer == target => {
+                addresses = peer_addresses.into_vec();
+            }
+            SwarmEvent::Behaviour(kad::Event::OutboundQueryProgressed {
+                result: kad::QueryResult::GetClosestPeers(result),
+                step,
+                ..
+            }) => {
+                if let Err(err) = result {
+                    log::warn!("DHT query failed: {err}");
+                }
+                if step.last {
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(addresses)
+}
+
+fn kad_behaviour(
+    local_peer_id: PeerId,
+    protocol: String,
+    timeout: u32,
+) -> Result<kad::Behaviour<kad::store::MemoryStore>> {
+    let mut config = kad::Config::default();
+    config
+        .set_protocol_names(vec![StreamProtocol::try_from_owned(protocol)?])
+        .set_query_timeout(Duration::from_secs(timeout as u64));
+    Ok(kad::Behaviour::with_config(
+        local_peer_id,
+        kad::store::MemoryStore::new(local_peer_id),
+        config,
+    ))
+}
+
+/// Maximum number of /dnsaddr TXT lookups performed when resolving a single address.
+const MAX_DNSADDR_LOOKUPS: usize = 32;
+
+/// Dial a peer, resolving a leading /dnsaddr component first so the concrete addresses
+/// being dialed are known.
+async fn dial<B>(swarm: &mut Swarm<B>, peer_addr: &str) -> Result<()>
+where
+    B: NetworkBehaviour,
+{
+    let addr: Multiaddr = peer_addr
+        .parse()
+        .with_context(|| format!("failed to parse peer address '{peer_addr}'"))?;
+    let addrs = resolve_dnsaddr(&addr).await?;
+    if addrs != [addr.clone()] {
+        for resolved in &addrs {
+            log::info!("resolved {addr} to {resolved}");
+        }
+    }
+    match addrs.as_slice() {
+        [single] => swarm.dial(single.clone())?,
+        _ => {
+            let peer_id = peer_id_from_addr(&addrs[0])?;
+            if addrs
+                .iter()
+                .any(|addr| peer_id_from_addr(addr).ok() != Some(peer_id))
+            {
+                bail!("{addr} resolves to multiple peers, add a /p2p/<peer id> to select one");
+            }
+            swarm.dial(DialOpts::peer_id(peer_id).addresses(addrs).build())?;
+        }
+    }
+    Ok(())
+}
+
+/// Resolve a multiaddr starting with /dnsaddr into the addresses from its TXT records.
+/// Other multiaddrs are returned unchanged.
+async fn resolve_dnsaddr(addr: &Multiaddr) -> Result<Vec<Multiaddr>> {
+    let Some(Protocol::Dnsaddr(name)) = addr.iter().next() else {
+        return Ok(vec![addr.clone()]);
+    };
+    let peer_id = peer_id_from_addr(addr).ok();
+    let resolver = TokioAsyncResolver::tokio_from_system_conf()?;
+    let mut resolved = Vec::new();
+    let mut pending = vec![name.to_string()];
+    let mut lookups = 0;
+    while let Some(name) = pending.pop() {
+        lookups += 1;
+        if lookups > MAX_DNSADDR_LOOKUPS {
+            bail!("too many /dnsaddr lookups resolving {addr}");
+        }
+        let txts = resolver
+            .txt_lookup(format!("_dnsaddr.{name}"))
+            .await
+            .with_context(|| format!("failed to resolve /dnsaddr/{name}"))?;
+        for txt in txts.iter() {
+            let data = txt.txt_data().concat();
+            let Some(entry) = std::str::from_utf8(&data)
+                .ok()
+                .and_then(|data| data.strip_prefix("dnsaddr="))
+            else {
+                continue;
+            };
+            let entry: Multiaddr = entry
+                .parse()
+                .with_context(|| format!("invalid dnsaddr record '{entry}' for {name}"))?;
+            if peer_id.is_some() && peer_id_from_addr(&entry).ok() != peer_id {
+                continue;
+            }
+            match entry.iter().next() {
+                Some(Protocol::Dnsaddr(name)) => pending.push(name.to_string()),
+                _ => resolved.push(entry),
+            }
+        }
+    }
+    if resolved.is_empty() {
+        bail!("no addresses found resolving {addr}");
+    }
+    Ok(resolved)
+}
+
+/// Get the peer ID from the trailing /p2p/<peer id> component of a multiaddr.
+fn peer_id_from_addr(addr: &Multiaddr) -> Result<PeerId> {
+    match addr.iter().last() {
+        Some(Protocol::P2p(peer_id)) => Ok(peer_id),
+        _ => bail!("{addr} does not end with a /p2p/<peer id> component"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn duration_stats() {
+        let stats = DurationStats::from_durations(&[ms(10), ms(30), ms(20)]).unwrap();
+        assert_eq!(stats.min, ms(10));
+        assert_eq!(stats.max, ms(30));
+        assert!((millis(stats.avg) - 20.0).abs() < 1e-6);
+        // Population standard deviation of 10, 20 and 30.
+        assert!((millis(stats.stddev) - (200.0f64 / 3.0).sqrt()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn duration_stats_empty() {
+        assert!(DurationStats::from_durations(&[]).is_none());
+    }
+
+    #[test]
+    fn loss() {
+        assert_eq!(loss_percent(0, 0), 0.0);
+        assert_eq!(loss_percent(4, 4), 0.0);
+        assert_eq!(loss_percent(4, 3), 25.0);
+        assert_eq!(loss_percent(4, 0), 100.0);
+    }
+
+    #[test]
+    fn summary() {
+        let summary = ping_summary("/ip4/127.0.0.1/tcp/4001", 4, &[ms(10), ms(30), ms(20)]);
+        let mut lines = summary.lines();
+        assert_eq!(
+            lines.next(),
+            Some("--- /ip4/127.0.0.1/tcp/4001 ping statistics ---")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("4 pings transmitted, 3 received, 25.0% loss")
+        );
+        // The average and deviation are computed with floats so only check min and max exactly.
+        let rtt = lines.next().unwrap();
+        assert!(rtt.starts_with("rtt min/avg/max/stddev = 10ms/"));
+        assert!(rtt.contains("/30ms/"));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn summary_without_responses() {
+        assert_eq!(
+            ping_summary("peer", 2, &[]),
+            "--- peer ping statistics ---\n2 pings transmitted, 0 received, 100.0% loss\n"
+        );
+    }
+}
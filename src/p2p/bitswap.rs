@@ -0,0 +1,357 @@
+//! Minimal bitswap client able to request a single block from a peer.
+//!
+//! Wants are sent on an outbound stream and the peer responds on a new inbound stream,
+//! so each stream carries exactly one length prefixed protobuf message.
+use std::{
+    collections::VecDeque,
+    io,
+    task::{Context, Poll},
+};
+
+use cid::Cid;
+use libp2p::{
+    core::{
+        upgrade::{InboundUpgrade, OutboundUpgrade, UpgradeInfo},
+        Endpoint,
+    },
+    futures::{future::BoxFuture, AsyncReadExt, AsyncWriteExt, FutureExt},
+    swarm::{
+        ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, NotifyHandler, OneShotHandler,
+        THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+    },
+    Multiaddr, PeerId, Stream, StreamProtocol,
+};
+use multihash_codetable::{Code, MultihashDigest};
+
+const PROTOCOLS: [StreamProtocol; 2] = [
+    StreamProtocol::new("/ipfs/bitswap/1.2.0"),
+    StreamProtocol::new("/ipfs/bitswap/1.1.0"),
+];
+
+/// Maximum size of a single bitswap message.
+const MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum Event {
+    /// A block matching the wanted CID was received.
+    Block { peer: PeerId, data: Vec<u8> },
+    /// The peer does not have the wanted block.
+    DontHave { peer: PeerId },
+    /// Sending the want to the peer failed.
+    Error { peer: PeerId, error: String },
+}
+
+/// Behaviour that requests a single block from every connected peer.
+pub struct Behaviour {
+    cid: Cid,
+    code: Code,
+    events: VecDeque<ToSwarm<Event, WantBlock>>,
+}
+
+impl Behaviour {
+    pub fn new(cid: Cid) -> anyhow::Result<Self> {
+        let code = Code::try_from(cid.hash().code())?;
+        Ok(Self {
+            cid,
+            code,
+            events: VecDeque::new(),
+        })
+    }
+
+    fn on_message(&mut self, peer: PeerId, message: Message) {
+        for data in message.blocks {
+            // Only the wanted block is reported, its hash verifies the data.
+            if self.code.digest(&data) == *self.cid.hash() {
+                self.events
+                    .push_back(ToSwarm::GenerateEvent(Event::Block { peer, data }));
+            }
+        }
+        if message.dont_have.contains(&self.cid) {
+            self.events
+                .push_back(ToSwarm::GenerateEvent(Event::DontHave { peer }));
+        }
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = OneShotHandler<Protocol, WantBlock, HandlerEvent>;
+    type ToSwarm = Event;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _connection_id: ConnectionId,
+        _peer: PeerId,
+        _local_addr: &Multiaddr,
+        _remote_addr: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(OneShotHandler::default())
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _connection_id: ConnectionId,
+        _peer: PeerId,
+        _addr: &Multiaddr,
+        _role_override: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(OneShotHandler::default())
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        if let FromSwarm::ConnectionEstablished(established) = event {
+            self.events.push_back(ToSwarm::NotifyHandler {
+                peer_id: established.peer_id,
+                handler: NotifyHandler::One(established.connection_id),
+                event: WantBlock(self.cid),
+            });
+        }
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        peer: PeerId,
+        _connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        match event {
+            Ok(HandlerEvent::Received(message)) => self.on_message(peer, message),
+            Ok(HandlerEvent::Sent) => {}
+            Err(err) => self.events.push_back(ToSwarm::GenerateEvent(Event::Error {
+                peer,
+                error: err.to_string(),
+            })),
+        }
+    }
+
+    fn poll(
+        &mut self,
+        _cx: &mut Context<'_>,
+    ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        match self.events.pop_front() {
+            Some(event) => Poll::Ready(event),
+            None => Poll::Pending,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum HandlerEvent {
+    Received(Message),
+    Sent,
+}
+
+impl From<Message> for HandlerEvent {
+    fn from(message: Message) -> Self {
+        HandlerEvent::Received(message)
+    }
+}
+
+impl From<()> for HandlerEvent {
+    fn from(_: ()) -> Self {
+        HandlerEvent::Sent
+    }
+}
+
+/// Blocks and block presences decoded from a bitswap message.
+#[derive(Debug, Default)]
+pub struct Message {
+    blocks: Vec<Vec<u8>>,
+    dont_have: Vec<Cid>,
+}
+
+impl Message {
+    fn decode(buf: &[u8]) -> io::Result<Self> {
+        let mut message = Message::default();
+        for (field, value) in decode_fields(buf)? {
+            match (field, value) {
+                // Bitswap 1.0.0 blocks
+                (2, Field::Bytes(data)) => message.blocks.push(data.to_vec()),
+                // Bitswap 1.1.0 blocks, the data is verified against the wanted CID so the
+                // prefix is not needed.
+                (3, Field::Bytes(block)) => {
+                    for (field, value) in decode_fields(block)? {
+                        if let (2, Field::Bytes(data)) = (field, value) {
+                            message.blocks.push(data.to_vec());
+                        }
+                    }
+                }
+                // Bitswap 1.2.0 block presences
+                (4, Field::Bytes(presence)) => {
+                    let mut cid = None;
+                    let mut dont_have = false;
+                    for (field, value) in decode_fields(presence)? {
+                        match (field, value) {
+                            (1, Field::Bytes(bytes)) => cid = Cid::try_from(bytes).ok(),
+                            (2, Field::Varint(presence_type)) => dont_have = presence_type == 1,
+                            _ => {}
+                        }
+                    }
+                    if let (Some(cid), true) = (cid, dont_have) {
+                        message.dont_have.push(cid);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(message)
+    }
+}
+
+/// Inbound bitswap stream carrying a single message from the peer.
+#[derive(Debug, Clone, Default)]
+pub struct Protocol;
+
+impl UpgradeInfo for Protocol {
+    type Info = StreamProtocol;
+    type InfoIter = [StreamProtocol; 2];
+
+    fn protocol_info(&self) -> Self::InfoIter {
+        PROTOCOLS
+    }
+}
+
+impl InboundUpgrade<Stream> for Protocol {
+    type Output = Message;
+    type Error = io::Error;
+    type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+    fn upgrade_inbound(self, mut socket: Stream, _info: Self::Info) -> Self::Future {
+        async move {
+            let len = read_varint(&mut socket).await? as usize;
+            if len > MAX_MESSAGE_SIZE {
+                return Err(invalid_data(format!("message of {len} bytes is too large")));
+            }
+            let mut buf = vec![0u8; len];
+            socket.read_exact(&mut buf).await?;
+            Message::decode(&buf)
+        }
+        .boxed()
+    }
+}
+
+/// Outbound bitswap stream sending a want for a single block.
+#[derive(Debug, Clone)]
+pub struct WantBlock(Cid);
+
+impl UpgradeInfo for WantBlock {
+    type Info = StreamProtocol;
+    type InfoIter = [StreamProtocol; 2];
+
+    fn protocol_info(&self) -> Self::InfoIter {
+        PROTOCOLS
+    }
+}
+
+impl OutboundUpgrade<Stream> for WantBlock {
+    type Output = ();
+    type Error = io::Error;
+    type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+    fn upgrade_outbound(self, mut socket: Stream, _info: Self::Info) -> Self::Future {
+        async move {
+            let mut entry = Vec::new();
+            encode_bytes_field(&mut entry, 1, &self.0.to_bytes());
+            // priority
+            encode_varint_field(&mut entry, 2, 1);
+            // sendDontHave
+            encode_varint_field(&mut entry, 5, 1);
+            let mut wantlist = Vec::new();
+            encode_bytes_field(&mut wantlist, 1, &entry);
+            let mut message = Vec::new();
+            encode_bytes_field(&mut message, 1, &wantlist);
+
+            let mut buf = Vec::new();
+            encode_varint(&mut buf, message.len() as u64);
+            buf.extend_from_slice(&message);
+            socket.write_all(&buf).await?;
+            socket.close().await
+        }
+        .boxed()
+    }
+}
+
+/// Value of a protobuf field, fixed size fields are skipped while decoding.
+enum Field<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+fn decode_fields(mut buf: &[u8]) -> io::Result<Vec<(u64, Field<'_>)>> {
+    let mut fields = Vec::new();
+    while !buf.is_empty() {
+        let key = decode_varint(&mut buf)?;
+        let value = match key & 0x7 {
+            0 => Field::Varint(decode_varint(&mut buf)?),
+            2 => {
+                let len = decode_varint(&mut buf)? as usize;
+                if len > buf.len() {
+                    return Err(invalid_data("truncated length delimited field"));
+                }
+                let (bytes, rest) = buf.split_at(len);
+                buf = rest;
+                Field::Bytes(bytes)
+            }
+            wire_type @ (1 | 5) => {
+                let len = if wire_type == 1 { 8 } else { 4 };
+                if len > buf.len() {
+                    return Err(invalid_data("truncated fixed size field"));
+                }
+                buf = &buf[len..];
+                continue;
+            }
+            wire_type => return Err(invalid_data(format!("unsupported wire type {wire_type}"))),
+        };
+        fields.push((key >> 3, value));
+    }
+    Ok(fields)
+}
+
+fn decode_varint(buf: &mut &[u8]) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let Some((byte, rest)) = buf.split_first() else {
+            return Err(invalid_data("truncated varint"));
+        };
+        *buf = rest;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("varint overflow"))
+}
+
+async fn read_varint(socket: &mut Stream) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0u8];
+        socket.read_exact(&mut byte).await?;
+        value |= ((byte[0] & 0x7f) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("varint overflow"))
+}
+
+fn encode_varint(buf: &mut Vec<u8>, value: u64) {
+    buf.extend_from_slice(unsigned_varint::encode::u64(
+        value,
+        &mut unsigned_varint::encode::u64_buffer(),
+    ));
+}
+
+fn encode_varint_field(buf: &mut Vec<u8>, field: u64, value: u64) {
+    encode_varint(buf, field << 3);
+    encode_varint(buf, value);
+}
+
+fn encode_bytes_field(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    encode_varint(buf, field << 3 | 2);
+    encode_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
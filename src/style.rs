@@ -0,0 +1,71 @@
+//! ANSI styling of text output, disabled unless color is enabled.
+use std::fmt::Display;
+
+use crate::cli::ColorChoice;
+
+const BOLD: &str = "\x1b[1m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Style {
+    enabled: bool,
+}
+
+impl Style {
+    /// Create a style for the color choice, auto enables color when writing to a terminal.
+    pub fn new(color: ColorChoice, is_terminal: bool) -> Self {
+        let enabled = match color {
+            ColorChoice::Auto => is_terminal,
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        };
+        Self { enabled }
+    }
+
+    /// Style a field label.
+    pub fn label(&self, label: impl Display) -> String {
+        self.paint(BOLD, label)
+    }
+
+    /// Style a CID or other content identifier.
+    pub fn cid(&self, cid: impl Display) -> String {
+        self.paint(GREEN, cid)
+    }
+
+    /// Style every Link(...) found in Debug formatted IPLD data.
+    pub fn debug_links(&self, text: &str) -> String {
+        if !self.enabled {
+            return text.to_string();
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("Link(") {
+            // Find the parenthesis closing the link, the CID may be formatted over many lines.
+            let mut depth = 0;
+            let Some(end) = rest[start..].find(|c| {
+                match c {
+                    '(' | '{' | '[' => depth += 1,
+                    ')' | '}' | ']' => depth -= 1,
+                    _ => {}
+                };
+                c == ')' && depth == 0
+            }) else {
+                break;
+            };
+            out.push_str(&rest[..start]);
+            out.push_str(&self.cid(&rest[start..=start + end]));
+            rest = &rest[start + end + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn paint(&self, code: &str, value: impl Display) -> String {
+        if self.enabled {
+            format!("{code}{value}{RESET}")
+        } else {
+            value.to_string()
+        }
+    }
+}